@@ -2,13 +2,27 @@
 fn main() {
     println!("cargo:warning=构建脚本开始执行");
 
+    // ssd_fw_core 的编码表来自其目录下的副本，必须与 config/fwver.yaml 一致
+    check_core_config();
+
+    // 生成 C 头文件
+    #[cfg(feature = "ffi")]
+    generate_c_header();
+
+    // 只在启用图形界面时处理 Windows 子系统与图标
+    if std::env::var("CARGO_FEATURE_GUI").is_err() {
+        println!("cargo:warning=未启用 gui 特性，跳过图标设置");
+        return;
+    }
+
     // 只在 Windows 平台上设置
     if std::env::var("CARGO_CFG_TARGET_OS").unwrap() == "windows" {
         println!("cargo:warning=检测到 Windows 平台");
 
         // 设置 Windows 子系统为 GUI（隐藏控制台窗口）
-        println!("cargo:rustc-link-arg=/SUBSYSTEM:WINDOWS");
-        println!("cargo:rustc-link-arg=/ENTRY:mainCRTStartup");
+        // 仅作用于可执行文件，避免影响 cdylib
+        println!("cargo:rustc-link-arg-bins=/SUBSYSTEM:WINDOWS");
+        println!("cargo:rustc-link-arg-bins=/ENTRY:mainCRTStartup");
 
         // 检查图标文件是否存在
         let icon_path = "assets/icon.ico";
@@ -39,4 +53,53 @@ MAINICON ICON "assets/icon.ico"
     } else {
         println!("cargo:warning=非 Windows 平台，跳过图标设置");
     }
-}
\ No newline at end of file
+}
+
+// 检查 ssd_fw_core/fwver.yaml 与 config/fwver.yaml 是否相同
+fn check_core_config() {
+    let config_path = "config/fwver.yaml";
+    let core_path = "ssd_fw_core/fwver.yaml";
+    // 声明 rerun-if-changed 后 cargo 只在列出的文件变化时重新运行，其余输入也要列出
+    for path in [config_path, core_path, "build.rs", "src/ffi.rs", "cbindgen.toml", "assets/icon.ico"] {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+
+    let config = std::fs::read_to_string(config_path).expect("无法读取 config/fwver.yaml");
+    let core = std::fs::read_to_string(core_path).expect("无法读取 ssd_fw_core/fwver.yaml");
+    if config != core {
+        panic!("ssd_fw_core/fwver.yaml 与 config/fwver.yaml 不一致，请将 config/fwver.yaml 复制到 ssd_fw_core/fwver.yaml");
+    }
+}
+
+// 使用 cbindgen 根据 src/ffi.rs 生成 C 头文件
+//
+// 头文件写入 OUT_DIR，不修改源码目录；设置环境变量 SSD_SN_TOOL_UPDATE_HEADER=1 时
+// 才同时更新仓库中的 include/ssd_sn_tool.h（ffi 测试会检查两者是否一致）。
+#[cfg(feature = "ffi")]
+fn generate_c_header() {
+    println!("cargo:rerun-if-env-changed=SSD_SN_TOOL_UPDATE_HEADER");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let config = match cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("cargo:warning=无法读取 cbindgen.toml: {}", e);
+            return;
+        }
+    };
+
+    // 只解析 src/ffi.rs，避免其他模块的公开常量混入 C 接口
+    match cbindgen::Builder::new()
+        .with_src(format!("{}/src/ffi.rs", crate_dir))
+        .with_config(config)
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/ssd_sn_tool.h", out_dir));
+            if std::env::var_os("SSD_SN_TOOL_UPDATE_HEADER").is_some() {
+                bindings.write_to_file(format!("{}/include/ssd_sn_tool.h", crate_dir));
+            }
+        }
+        Err(e) => println!("cargo:warning=C 头文件生成失败: {}", e),
+    }
+}
@@ -0,0 +1,71 @@
+/*
+ * C ABI 接口测试程序
+ *
+ * 编译（Linux）:
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib,staticlib
+ *   gcc examples/c/ffi_test.c -Iinclude -Ltarget/release -lssd_sn_tool -o ffi_test
+ *   LD_LIBRARY_PATH=target/release ./ffi_test
+ */
+#include <stdio.h>
+#include <string.h>
+#include "ssd_sn_tool.h"
+
+static int failures = 0;
+
+#define CHECK(cond, msg)                                   \
+    do {                                                   \
+        if (!(cond)) {                                     \
+            printf("FAIL: %s (%s:%d)\n", msg, __FILE__, __LINE__); \
+            failures++;                                    \
+        } else {                                           \
+            printf("ok:   %s\n", msg);                     \
+        }                                                  \
+    } while (0)
+
+int main(void) {
+    char code[SSD_SN_FIRMWARE_CODE_LEN + 1];
+    char date_code[SSD_SN_DATE_CODE_LEN + 1];
+    SsdFirmwareFields fields = {2025, 12, 1, 1, 1024, 'A', 4};
+    SsdFirmwareFields parsed;
+    SsdDate date;
+    int32_t status;
+
+    /* 生成 */
+    status = ssd_sn_generate_firmware_code(&fields, code, sizeof(code));
+    CHECK(status == SSD_SN_OK, "generate firmware code");
+    CHECK(strcmp(code, "S5ID13A4") == 0, "generated code is S5ID13A4");
+
+    /* 解析 */
+    status = ssd_sn_parse_firmware_code(code, &parsed);
+    CHECK(status == SSD_SN_OK, "parse firmware code");
+    CHECK(parsed.year == 2025 && parsed.month == 12 && parsed.day == 1, "parsed date");
+    CHECK(parsed.pcb_size == 1 && parsed.dram_size_mb == 1024, "parsed pcb/dram");
+    CHECK(parsed.package_code == 'A' && parsed.chip_count == 4, "parsed package/chips");
+    status = ssd_sn_parse_firmware_code_era(code, 0, &parsed);
+    CHECK(status == SSD_SN_OK && parsed.year == 2025, "parse firmware code in era 0");
+    status = ssd_sn_parse_firmware_code_era(code, 99, &parsed);
+    CHECK(status == SSD_SN_ERR_INVALID_PARAMETER, "invalid era");
+
+    /* 日期编码 */
+    status = ssd_sn_date_to_code(2025, 12, 1, date_code, sizeof(date_code));
+    CHECK(status == SSD_SN_OK && strcmp(date_code, "5ID") == 0, "date to code");
+    status = ssd_sn_code_to_date("5ID", &date);
+    CHECK(status == SSD_SN_OK && date.year == 2025 && date.month == 12 && date.day == 1, "code to date");
+
+    /* 错误码 */
+    status = ssd_sn_generate_firmware_code(&fields, code, 4);
+    CHECK(status == SSD_SN_ERR_BUFFER_TOO_SMALL, "buffer too small");
+    status = ssd_sn_parse_firmware_code("X1234567", &parsed);
+    CHECK(status == SSD_SN_ERR_FORMAT, "invalid format");
+    status = ssd_sn_date_to_code(2009, 1, 1, date_code, sizeof(date_code));
+    CHECK(status == SSD_SN_ERR_DATE_CODE, "date out of range");
+    fields.chip_count = 99;
+    status = ssd_sn_generate_firmware_code(&fields, code, sizeof(code));
+    CHECK(status == SSD_SN_ERR_INVALID_PARAMETER, "invalid chip count");
+    status = ssd_sn_parse_firmware_code(NULL, &parsed);
+    CHECK(status == SSD_SN_ERR_NULL_POINTER, "null pointer");
+    CHECK(strcmp(ssd_sn_status_name(SSD_SN_ERR_FORMAT), "SSD_SN_ERR_FORMAT") == 0, "status name");
+
+    printf("%s (%d failures)\n", failures ? "FAILED" : "PASSED", failures);
+    return failures ? 1 : 0;
+}
@@ -0,0 +1,105 @@
+/* 由 cbindgen 自动生成，请勿手动修改 */
+
+#ifndef SSD_SN_TOOL_H
+#define SSD_SN_TOOL_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define SSD_SN_OK 0
+
+#define SSD_SN_ERR_CONFIG 1
+
+#define SSD_SN_ERR_DATE_CODE 2
+
+#define SSD_SN_ERR_FORMAT 3
+
+#define SSD_SN_ERR_INVALID_PARAMETER 4
+
+#define SSD_SN_ERR_NULL_POINTER 5
+
+#define SSD_SN_ERR_BUFFER_TOO_SMALL 6
+
+#define SSD_SN_ERR_INTERNAL 7
+
+#define SSD_SN_FIRMWARE_CODE_LEN 8
+
+#define SSD_SN_DATE_CODE_LEN 3
+
+typedef struct SsdFirmwareFields {
+  int32_t year;
+  uint32_t month;
+  uint32_t day;
+  uint8_t pcb_size;
+  int32_t dram_size_mb;
+  char package_code;
+  uint8_t chip_count;
+} SsdFirmwareFields;
+
+typedef struct SsdDate {
+  int32_t year;
+  uint32_t month;
+  uint32_t day;
+} SsdDate;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * 生成固件版本号，写入 `out`（至少 9 字节）。
+ *
+ * # Safety
+ * `fields` 必须指向有效的结构体，`out` 必须指向至少 `out_len` 字节的可写缓冲区。
+ */
+int32_t ssd_sn_generate_firmware_code(const struct SsdFirmwareFields *fields,
+                                      char *out,
+                                      size_t out_len);
+
+/**
+ * 解析以 '\0' 结尾的固件版本号（当前纪元），结果写入 `out`。
+ *
+ * # Safety
+ * `code` 必须是有效的 C 字符串，`out` 必须指向可写的结构体。
+ */
+int32_t ssd_sn_parse_firmware_code(const char *code, struct SsdFirmwareFields *out);
+
+/**
+ * 按指定纪元解析固件版本号（纪元切换后同一编码对应不同日期）。
+ *
+ * # Safety
+ * `code` 必须是有效的 C 字符串，`out` 必须指向可写的结构体。
+ */
+int32_t ssd_sn_parse_firmware_code_era(const char *code,
+                                       uint32_t era,
+                                       struct SsdFirmwareFields *out);
+
+/**
+ * 将日期编码为 3 位日期编码，写入 `out`（至少 4 字节）。
+ *
+ * # Safety
+ * `out` 必须指向至少 `out_len` 字节的可写缓冲区。
+ */
+int32_t ssd_sn_date_to_code(int32_t year, uint32_t month, uint32_t day, char *out, size_t out_len);
+
+/**
+ * 将以 '\0' 结尾的 3 位日期编码解码为日期，结果写入 `out`。
+ *
+ * # Safety
+ * `code` 必须是有效的 C 字符串，`out` 必须指向可写的结构体。
+ */
+int32_t ssd_sn_code_to_date(const char *code, struct SsdDate *out);
+
+/**
+ * 返回状态码对应的名称（静态字符串，调用方无需释放）。
+ */
+const char *ssd_sn_status_name(int32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SSD_SN_TOOL_H */
@@ -0,0 +1,298 @@
+use std::path::Path;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use crate::error::SnError;
+use crate::firmware_codec::{FirmwareCodec, FirmwareFields};
+
+// 默认目录文件
+pub const DEFAULT_CATALOG: &str = "firmware_catalog.yaml";
+
+// 发布状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseStatus {
+    Beta,
+    Released,
+    Withdrawn,
+}
+
+impl ReleaseStatus {
+    pub const ALL: [ReleaseStatus; 3] = [ReleaseStatus::Beta, ReleaseStatus::Released, ReleaseStatus::Withdrawn];
+
+    pub fn from_name(name: &str) -> Result<Self, SnError> {
+        match name.to_ascii_lowercase().as_str() {
+            "beta" => Ok(ReleaseStatus::Beta),
+            "released" => Ok(ReleaseStatus::Released),
+            "withdrawn" => Ok(ReleaseStatus::Withdrawn),
+            _ => Err(SnError::InvalidParameter(format!("无效的发布状态: {} (可选 beta|released|withdrawn)", name))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReleaseStatus::Beta => "beta",
+            ReleaseStatus::Released => "released",
+            ReleaseStatus::Withdrawn => "withdrawn",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReleaseStatus::Beta => "测试版",
+            ReleaseStatus::Released => "已发布",
+            ReleaseStatus::Withdrawn => "已撤回",
+        }
+    }
+}
+
+// 目录记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogRecord {
+    pub code: String,
+    pub status: ReleaseStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    // 由固件版本号解析得到的字段（加载时建立索引）
+    #[serde(skip)]
+    pub fields: Option<FirmwareFields>,
+}
+
+impl CatalogRecord {
+    pub fn new(code: &str, status: ReleaseStatus) -> Result<Self, SnError> {
+        let code = code.trim().to_ascii_uppercase();
+        let fields = FirmwareCodec::parse_firmware_code(&code)?;
+        Ok(Self {
+            code,
+            status,
+            file: None,
+            sha256: None,
+            notes: String::new(),
+            fields: Some(fields),
+        })
+    }
+
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.fields.and_then(|(y, m, d, ..)| NaiveDate::from_ymd_opt(y, m, d))
+    }
+}
+
+// 查询条件（未设置的条件不参与过滤）
+#[derive(Debug, Clone, Default)]
+pub struct CatalogQuery {
+    pub pcb_size: Option<u8>,
+    pub dram_size_mb: Option<i32>,
+    pub package_code: Option<char>,
+    pub chip_count: Option<u8>,
+    pub status: Option<ReleaseStatus>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    // 只返回最新的一条
+    pub latest: bool,
+}
+
+impl CatalogQuery {
+    pub fn matches(&self, record: &CatalogRecord) -> bool {
+        let Some((_, _, _, pcb_size, dram_size_mb, package_code, chip_count)) = record.fields else {
+            return false;
+        };
+        let date = record.date();
+
+        self.pcb_size.is_none_or(|v| v == pcb_size)
+            && self.dram_size_mb.is_none_or(|v| v == dram_size_mb)
+            && self.package_code.is_none_or(|v| v.eq_ignore_ascii_case(&package_code))
+            && self.chip_count.is_none_or(|v| v == chip_count)
+            && self.status.is_none_or(|v| v == record.status)
+            && self.from.is_none_or(|v| date.is_some_and(|d| d >= v))
+            && self.to.is_none_or(|v| date.is_some_and(|d| d <= v))
+    }
+
+    // 设置季度范围，格式如 2025-Q3
+    pub fn set_quarter(&mut self, quarter: &str) -> Result<(), SnError> {
+        let err = || SnError::InvalidParameter(format!("无效的季度 (应为 YYYY-Qn): {}", quarter));
+        let (year, q) = quarter.to_ascii_uppercase()
+            .split_once("-Q")
+            .and_then(|(y, q)| Some((y.parse::<i32>().ok()?, q.parse::<u32>().ok()?)))
+            .ok_or_else(err)?;
+        if !(1..=4).contains(&q) {
+            return Err(err());
+        }
+
+        let from = NaiveDate::from_ymd_opt(year, (q - 1) * 3 + 1, 1).ok_or_else(err)?;
+        let next = if q == 4 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(year, q * 3 + 1, 1)
+        }.ok_or_else(err)?;
+
+        self.from = Some(from);
+        self.to = next.pred_opt();
+        Ok(())
+    }
+}
+
+// 固件发布目录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Catalog {
+    #[serde(default)]
+    pub records: Vec<CatalogRecord>,
+}
+
+impl Catalog {
+    // 加载目录，文件不存在时返回空目录
+    pub fn load(path: &Path) -> Result<Self, SnError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SnError::InvalidParameter(format!("无法读取目录文件 {}: {}", path.display(), e)))?;
+        let mut catalog: Catalog = serde_yaml::from_str(&content)
+            .map_err(|e| SnError::ConfigError(format!("目录文件格式错误: {}", e)))?;
+        catalog.reindex();
+        Ok(catalog)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SnError> {
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| SnError::ConfigError(format!("目录序列化失败: {}", e)))?;
+        std::fs::write(path, content)
+            .map_err(|e| SnError::InvalidParameter(format!("无法写入目录文件 {}: {}", path.display(), e)))
+    }
+
+    // 重新解析所有记录的固件版本号
+    pub fn reindex(&mut self) {
+        for record in &mut self.records {
+            record.fields = FirmwareCodec::parse_firmware_code(&record.code).ok();
+        }
+    }
+
+    pub fn find(&self, code: &str) -> Option<&CatalogRecord> {
+        self.records.iter().find(|r| r.code.eq_ignore_ascii_case(code.trim()))
+    }
+
+    pub fn add(&mut self, record: CatalogRecord) -> Result<(), SnError> {
+        if self.find(&record.code).is_some() {
+            return Err(SnError::InvalidParameter(format!("目录中已存在 {}", record.code)));
+        }
+        self.records.push(record);
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, code: &str, status: ReleaseStatus) -> Result<(), SnError> {
+        let record = self.records.iter_mut()
+            .find(|r| r.code.eq_ignore_ascii_case(code.trim()))
+            .ok_or_else(|| SnError::InvalidParameter(format!("目录中没有 {}", code)))?;
+        record.status = status;
+        Ok(())
+    }
+
+    // 查询，结果按日期从新到旧
+    pub fn query(&self, query: &CatalogQuery) -> Vec<&CatalogRecord> {
+        let mut results: Vec<&CatalogRecord> = self.records.iter()
+            .filter(|r| query.matches(r))
+            .collect();
+        results.sort_by(|a, b| b.date().cmp(&a.date()).then_with(|| a.code.cmp(&b.code)));
+        if query.latest {
+            results.truncate(1);
+        }
+        results
+    }
+
+    // 无法解析的记录（配置变更后可能出现）
+    pub fn invalid_records(&self) -> Vec<&CatalogRecord> {
+        self.records.iter().filter(|r| r.fields.is_none()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CONFIG;
+
+    fn record(date: (i32, u32, u32), dram_size_mb: i32, package: &str, status: ReleaseStatus) -> CatalogRecord {
+        let package = CONFIG.firmware.find_package(package).unwrap();
+        let code = FirmwareCodec::generate_firmware_code(date.0, date.1, date.2, 1, dram_size_mb, package, 4).unwrap();
+        CatalogRecord::new(&code, status).unwrap()
+    }
+
+    fn catalog() -> Catalog {
+        Catalog {
+            records: vec![
+                record((2025, 1, 10), -1, "BGA132", ReleaseStatus::Released),
+                record((2025, 5, 2), -1, "BGA132", ReleaseStatus::Released),
+                record((2025, 6, 1), -1, "BGA132", ReleaseStatus::Beta),
+                record((2025, 7, 1), 1024, "BGA132", ReleaseStatus::Released),
+                record((2025, 7, 1), -1, "BGA132", ReleaseStatus::Withdrawn),
+                record((2025, 9, 30), -1, "BGA100", ReleaseStatus::Withdrawn),
+                record((2025, 10, 1), -1, "BGA100", ReleaseStatus::Withdrawn),
+            ],
+        }
+    }
+
+    fn dates(results: &[&CatalogRecord]) -> Vec<NaiveDate> {
+        results.iter().filter_map(|r| r.date()).collect()
+    }
+
+    #[test]
+    fn latest_released_for_hardware() {
+        let query = CatalogQuery {
+            pcb_size: Some(1),
+            dram_size_mb: Some(-1),
+            package_code: Some(CONFIG.firmware.find_package("BGA132").unwrap()),
+            chip_count: Some(4),
+            status: Some(ReleaseStatus::Released),
+            latest: true,
+            ..Default::default()
+        };
+        let catalog = catalog();
+        assert_eq!(dates(&catalog.query(&query)), [NaiveDate::from_ymd_opt(2025, 5, 2).unwrap()]);
+
+        let all = CatalogQuery { latest: false, ..query };
+        assert_eq!(catalog.query(&all).len(), 2);
+    }
+
+    #[test]
+    fn withdrawn_in_quarter() {
+        let mut query = CatalogQuery { status: Some(ReleaseStatus::Withdrawn), ..Default::default() };
+        query.set_quarter("2025-q3").unwrap();
+        let catalog = catalog();
+        let found = catalog.query(&query);
+        // 结果按日期从新到旧，季度末当天包含在内，下一季度第一天不包含
+        assert_eq!(dates(&found), [
+            NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+        ]);
+    }
+
+    #[test]
+    fn quarter_ranges() {
+        let mut query = CatalogQuery::default();
+        query.set_quarter("2024-Q1").unwrap();
+        assert_eq!((query.from, query.to), (NaiveDate::from_ymd_opt(2024, 1, 1), NaiveDate::from_ymd_opt(2024, 3, 31)));
+        query.set_quarter("2025-Q4").unwrap();
+        assert_eq!((query.from, query.to), (NaiveDate::from_ymd_opt(2025, 10, 1), NaiveDate::from_ymd_opt(2025, 12, 31)));
+
+        for bad in ["2025-Q0", "2025-Q5", "2025Q3", "Q3-2025", "abc-Q1"] {
+            assert!(query.set_quarter(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn unparsable_records_never_match() {
+        let mut catalog = catalog();
+        catalog.records.push(CatalogRecord {
+            code: "SZZZZZZZ".to_string(),
+            status: ReleaseStatus::Released,
+            file: None,
+            sha256: None,
+            notes: String::new(),
+            fields: None,
+        });
+        assert_eq!(catalog.invalid_records().len(), 1);
+        assert_eq!(catalog.query(&CatalogQuery::default()).len(), 7);
+        assert!(catalog.add(record((2025, 1, 10), -1, "BGA132", ReleaseStatus::Beta)).is_err());
+    }
+}
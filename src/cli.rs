@@ -0,0 +1,754 @@
+use std::cmp::Ordering;
+use std::io::Write;
+use chrono::{Datelike, Months, NaiveDate};
+use crate::catalog::{self, Catalog, CatalogQuery, CatalogRecord, ReleaseStatus};
+use crate::code_compare::{Comparison, Verdict};
+use crate::code_pattern::CodePattern;
+use crate::codegen::{Codebook, Lang};
+use crate::config::CONFIG;
+use crate::date_calendar::{self, CalendarFormat};
+use crate::error::SnError;
+use crate::factory_clock::{self, SystemClock};
+use crate::firmware_codec::FirmwareCodec;
+use crate::fw_organize::{self, OrganizeMode};
+use crate::fw_repo;
+use crate::image_patch::{self, ImageLayout};
+use crate::preset::{self, Presets};
+use crate::server::ApiServer;
+use crate::stamp::{self, BoardDescriptor, StampFormat};
+
+// 命令行入口，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+
+    let result = match args.as_slice() {
+        ["firmware", "generate", rest @ ..] if rest.contains(&"--preset") => firmware_generate_preset(rest),
+        ["firmware", "generate", rest @ ..] => firmware_generate(rest),
+        ["firmware", "presets", rest @ ..] => firmware_presets(rest),
+        ["firmware", "parse", rest @ ..] => firmware_parse(rest),
+        ["firmware", "stamp", rest @ ..] => firmware_stamp(rest),
+        ["firmware", "patch-image", rest @ ..] => firmware_patch_image(rest),
+        ["firmware", "select", rest @ ..] => firmware_select(rest),
+        ["firmware", "organize", rest @ ..] => firmware_organize(rest),
+        ["firmware", "search", rest @ ..] => firmware_search(rest),
+        ["firmware", "calendar", rest @ ..] => firmware_calendar(rest),
+        ["firmware", "date-diff", first, second] => firmware_date_diff(first, second),
+        ["firmware", "compare", rest @ ..] => firmware_compare(rest),
+        ["firmware", "age", rest @ ..] => firmware_age(rest),
+        ["firmware", "config"] => {
+            println!("{}", CONFIG.firmware.get_config_info());
+            Ok(())
+        }
+        ["catalog", rest @ ..] => catalog_command(rest),
+        ["serve", rest @ ..] => serve(rest),
+        ["codegen", rest @ ..] => codegen(rest),
+        _ => {
+            FirmwareCodec::print_usage();
+            return 2;
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn firmware_generate(args: &[&str]) -> Result<(), SnError> {
+    // 日期可以写成 <年> <月> <日>，也可以是一个 YYYY-MM-DD / today 参数
+    let (year, month, day, rest) = match args.len() {
+        7 => (parse_arg(args[0], "年份")?, parse_arg(args[1], "月份")?, parse_arg(args[2], "日期")?, &args[3..]),
+        5 => {
+            let date = parse_date_arg(args[0])?;
+            (date.year(), date.month(), date.day(), &args[1..])
+        }
+        _ => return Err(SnError::InvalidParameter(
+            "用法: firmware generate (<年> <月> <日> | <YYYY-MM-DD> | today) <PCB尺寸> <DRAM大小> <封装代码> <颗粒数>".to_string()
+        )),
+    };
+
+    let pcb_size = parse_arg(rest[0], "PCB尺寸")?;
+    let dram_size_mb = FirmwareCodec::parse_dram_size(rest[1])?;
+    let package_code = parse_arg(rest[2], "封装代码")?;
+    let chip_count = parse_arg(rest[3], "颗粒数")?;
+
+    let code = FirmwareCodec::generate_firmware_code(
+        year, month, day, pcb_size, dram_size_mb, package_code, chip_count
+    )?;
+    println!("{}", code);
+    Ok(())
+}
+
+// 按预设生成：firmware generate --preset <名称> [日期]，日期默认为当前生产日期
+fn firmware_generate_preset(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware generate --preset <名称> [<YYYY-MM-DD> | today | <年> <月> <日>] [--presets <文件>]".to_string()
+    );
+
+    let mut name = None;
+    let mut path = preset::DEFAULT_PRESETS;
+    let mut date_args = Vec::new();
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--preset" => name = Some(*iter.next().ok_or_else(usage)?),
+            "--presets" => path = iter.next().ok_or_else(usage)?,
+            _ => date_args.push(arg),
+        }
+    }
+    let name = name.ok_or_else(usage)?;
+    let date = match date_args.as_slice() {
+        [] => parse_date_arg("today")?,
+        [date] => parse_date_arg(date)?,
+        [year, month, day] => NaiveDate::from_ymd_opt(parse_arg(year, "年份")?, parse_arg(month, "月份")?, parse_arg(day, "日期")?)
+            .ok_or_else(|| SnError::DateCodeError("无效的目标日期".to_string()))?,
+        _ => return Err(usage()),
+    };
+
+    let (presets, warnings) = Presets::load(std::path::Path::new(path))?;
+    for warning in warnings {
+        eprintln!("警告: {}", warning);
+    }
+    let code = presets.get(name)?.generate(date)?;
+    println!("{}", code);
+    Ok(())
+}
+
+// 列出预设，并对引用了已停用编码表项的预设给出警告
+fn firmware_presets(args: &[&str]) -> Result<(), SnError> {
+    let path = match args {
+        [] => preset::DEFAULT_PRESETS,
+        ["--presets", path] => path,
+        _ => return Err(SnError::InvalidParameter("用法: firmware presets [--presets <文件>]".to_string())),
+    };
+    let (presets, warnings) = Presets::load(std::path::Path::new(path))?;
+    if presets.presets.is_empty() {
+        println!("{} 中没有预设", path);
+        return Ok(());
+    }
+    for preset in &presets.presets {
+        println!("{}: {}", preset.name, preset.summary());
+    }
+    for warning in warnings {
+        eprintln!("警告: {}", warning);
+    }
+    Ok(())
+}
+
+fn firmware_parse(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter("用法: firmware parse <固件版本号> [--era N]".to_string());
+    let (code, era) = match args {
+        [code] => (*code, None),
+        [code, "--era", era] => (*code, Some(parse_arg::<usize>(era, "纪元")?)),
+        _ => return Err(usage()),
+    };
+
+    let (year, month, day, pcb_size, dram_size_mb, package_code, chip_count) =
+        FirmwareCodec::parse_firmware_code_in_era(code, era.unwrap_or(CONFIG.firmware.era))?;
+    let dram_code = FirmwareCodec::dram_size_to_code(dram_size_mb)?;
+
+    println!("固件版本号: {}", code.to_ascii_uppercase());
+    println!("生产日期: {}-{:02}-{:02}", year, month, day);
+    println!("PCB尺寸: {} ({})", pcb_size, CONFIG.firmware.get_pcb_size_name(pcb_size));
+    println!("DRAM大小: {}", CONFIG.firmware.get_dram_size_desc(dram_code));
+    println!("封装类型: {} ({})", package_code.to_ascii_uppercase(), CONFIG.firmware.get_package_name(package_code));
+    println!("颗粒数量: {}", chip_count);
+
+    // 存在多个纪元且未指定纪元时，列出其他纪元下的日期
+    if era.is_none() && CONFIG.firmware.era_count() > 1 {
+        for other in (0..CONFIG.firmware.era_count()).filter(|&e| e != CONFIG.firmware.era) {
+            if let Ok((y, m, d)) = FirmwareCodec::code_to_date_in_era(&code[1..4], other) {
+                println!("  纪元 {} 下为: {}-{:02}-{:02}", other, y, m, d);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn firmware_stamp(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware stamp --board <描述文件> [--date YYYY-MM-DD|today] [--format c|env|rust] [--name <名称>] [--output <文件>]".to_string()
+    );
+
+    let mut board_path = None;
+    let mut date = None;
+    let mut format = StampFormat::CHeader;
+    let mut name = None;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--board" => board_path = Some(*iter.next().ok_or_else(usage)?),
+            "--date" => date = Some(*iter.next().ok_or_else(usage)?),
+            "--format" => format = StampFormat::from_name(iter.next().ok_or_else(usage)?)?,
+            "--name" => name = Some(*iter.next().ok_or_else(usage)?),
+            "--output" | "-o" => output = Some(*iter.next().ok_or_else(usage)?),
+            _ => return Err(usage()),
+        }
+    }
+
+    let name = name.unwrap_or(format.default_name());
+    stamp::validate_name(name)?;
+    let board = BoardDescriptor::load(std::path::Path::new(board_path.ok_or_else(usage)?))?;
+    let (year, month, day) = stamp::resolve_stamp_date(date)?;
+    let code = board.generate(year, month, day)?;
+    let content = stamp::render_stamp(&code, format, name, &board)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, content)
+                .map_err(|e| SnError::InvalidParameter(format!("无法写入文件 {}: {}", path, e)))?;
+            eprintln!("{} -> {}", code, path);
+            Ok(())
+        }
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+fn firmware_patch_image(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware patch-image <镜像> --board <描述文件> [--date YYYY-MM-DD|today] [--layout <布局文件>] (--output <文件> | --dry-run)".to_string()
+    );
+
+    let (input, rest) = args.split_first().ok_or_else(usage)?;
+    let mut board_path = None;
+    let mut date = None;
+    let mut layout_path = None;
+    let mut output = None;
+    let mut dry_run = false;
+    let mut iter = rest.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--board" => board_path = Some(*iter.next().ok_or_else(usage)?),
+            "--date" => date = Some(*iter.next().ok_or_else(usage)?),
+            "--layout" => layout_path = Some(*iter.next().ok_or_else(usage)?),
+            "--output" | "-o" => output = Some(*iter.next().ok_or_else(usage)?),
+            "--dry-run" => dry_run = true,
+            _ => return Err(usage()),
+        }
+    }
+    if output.is_none() && !dry_run {
+        return Err(usage());
+    }
+
+    let board = BoardDescriptor::load(std::path::Path::new(board_path.ok_or_else(usage)?))?;
+    let layout = match layout_path {
+        Some(path) => ImageLayout::load(std::path::Path::new(path))?,
+        None => ImageLayout::default(),
+    };
+    let mut data = std::fs::read(input)
+        .map_err(|e| SnError::InvalidParameter(format!("无法读取镜像 {}: {}", input, e)))?;
+
+    // 未指定日期时沿用原版本号的日期
+    let (year, month, day) = match date {
+        Some(_) => stamp::resolve_stamp_date(date)?,
+        None => {
+            let (_, old_code) = image_patch::locate_code(&data, &layout)?;
+            let (year, month, day, ..) = FirmwareCodec::parse_firmware_code(&old_code)?;
+            (year, month, day)
+        }
+    };
+    let new_code = board.generate(year, month, day)?;
+
+    let report = image_patch::patch_image(&mut data, &layout, &new_code)?;
+    println!("偏移 0x{:X}: {} -> {}", report.offset, report.old_code, report.new_code);
+    for update in &report.checksums {
+        println!(
+            "{} @0x{:X}: 0x{:X} -> 0x{:X}",
+            update.kind.name(), update.store_at, update.old_value, update.new_value
+        );
+    }
+
+    match output {
+        Some(path) if !dry_run => std::fs::write(path, &data)
+            .map_err(|e| SnError::InvalidParameter(format!("无法写入文件 {}: {}", path, e))),
+        _ => {
+            println!("(dry-run，未写入文件)");
+            Ok(())
+        }
+    }
+}
+
+fn firmware_select(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware select --repo <目录> (--code <固件版本号> | --identify <identify文件>)".to_string()
+    );
+
+    let mut repo = None;
+    let mut code = None;
+    let mut identify = None;
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--repo" => repo = Some(*iter.next().ok_or_else(usage)?),
+            "--code" => code = Some(iter.next().ok_or_else(usage)?.to_string()),
+            "--identify" => identify = Some(*iter.next().ok_or_else(usage)?),
+            _ => return Err(usage()),
+        }
+    }
+
+    let code = match (code, identify) {
+        (Some(code), None) => code,
+        (None, Some(path)) => {
+            let data = std::fs::read(path)
+                .map_err(|e| SnError::InvalidParameter(format!("无法读取 {}: {}", path, e)))?;
+            fw_repo::firmware_code_from_identify(&data)?
+        }
+        _ => return Err(usage()),
+    };
+    let target = FirmwareCodec::parse_firmware_code(&code)?;
+
+    let (images, mut rejected) = fw_repo::index_repository(std::path::Path::new(repo.ok_or_else(usage)?))?;
+    let selection = fw_repo::select(&target, images);
+    rejected.extend(selection.rejected);
+
+    println!("当前固件版本号: {}", code.to_ascii_uppercase());
+    match &selection.best {
+        Some(best) => println!("推荐镜像: {} ({})", best.path.display(), best.code),
+        None => println!("未找到兼容镜像"),
+    }
+
+    if !selection.compatible.is_empty() {
+        println!("\n兼容镜像（按日期从新到旧）:");
+        for image in &selection.compatible {
+            println!(
+                "  {} {}-{:02}-{:02} {}",
+                image.code, image.fields.0, image.fields.1, image.fields.2, image.path.display()
+            );
+        }
+    }
+
+    if !rejected.is_empty() {
+        println!("\n已排除:");
+        for r in &rejected {
+            println!(
+                "  {} [{}] {}",
+                r.path.display(),
+                r.code.as_deref().unwrap_or("-"),
+                r.reasons.join("; ")
+            );
+        }
+    }
+
+    match selection.best {
+        Some(_) => Ok(()),
+        None => Err(SnError::InvalidParameter("没有兼容的固件镜像".to_string())),
+    }
+}
+
+fn firmware_organize(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware organize <源目录> --dest <目标目录> [--template <模板>] [--move] [--dry-run]".to_string()
+    );
+
+    let (source, rest) = args.split_first().ok_or_else(usage)?;
+    let mut dest = None;
+    let mut template = fw_organize::DEFAULT_TEMPLATE;
+    let mut mode = OrganizeMode::Copy;
+    let mut dry_run = false;
+    let mut iter = rest.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--dest" => dest = Some(*iter.next().ok_or_else(usage)?),
+            "--template" => template = iter.next().ok_or_else(usage)?,
+            "--move" => mode = OrganizeMode::Move,
+            "--copy" => mode = OrganizeMode::Copy,
+            "--dry-run" => dry_run = true,
+            _ => return Err(usage()),
+        }
+    }
+    let dest = std::path::Path::new(dest.ok_or_else(usage)?);
+
+    let (entries, mut skipped) = fw_organize::plan(std::path::Path::new(source), template)?;
+    for entry in &entries {
+        println!(
+            "{} [{}, 来自{}] -> {}",
+            entry.source.display(),
+            entry.code,
+            entry.found_in.name(),
+            dest.join(&entry.target).display()
+        );
+    }
+
+    if dry_run {
+        println!("(dry-run，未修改文件)");
+    } else {
+        let failed = fw_organize::execute(&entries, dest, mode)?;
+        let done: Vec<_> = entries.iter()
+            .filter(|e| !failed.iter().any(|f| f.path == e.source))
+            .cloned()
+            .collect();
+        skipped.extend(failed);
+
+        // 合并到已有清单
+        let manifest_path = dest.join(fw_organize::MANIFEST_FILE);
+        let mut manifest = std::fs::read_to_string(&manifest_path).unwrap_or_default();
+        for line in fw_organize::manifest(&done).lines() {
+            if !manifest.lines().any(|l| l == line) {
+                manifest.push_str(line);
+                manifest.push('\n');
+            }
+        }
+        if !done.is_empty() {
+            std::fs::write(&manifest_path, manifest)
+                .map_err(|e| SnError::InvalidParameter(format!("无法写入清单 {}: {}", manifest_path.display(), e)))?;
+        }
+        println!("已处理 {} 个文件，清单: {}", done.len(), manifest_path.display());
+    }
+
+    for s in &skipped {
+        println!("跳过 {}: {}", s.path.display(), s.reason);
+    }
+    Ok(())
+}
+
+fn firmware_search(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware search <模式> [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--count] [--limit N] [--filter <文件>|-]".to_string()
+    );
+
+    let (pattern, rest) = args.split_first().ok_or_else(usage)?;
+    let (mut from, mut to) = (None, None);
+    let mut count_only = false;
+    let mut limit = None;
+    let mut filter = None;
+    let mut iter = rest.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--from" => from = Some(parse_date_arg(iter.next().ok_or_else(usage)?)?),
+            "--to" => to = Some(parse_date_arg(iter.next().ok_or_else(usage)?)?),
+            "--count" => count_only = true,
+            "--limit" => limit = Some(parse_arg::<usize>(iter.next().ok_or_else(usage)?, "数量")?),
+            "--filter" => filter = Some(*iter.next().ok_or_else(usage)?),
+            _ => return Err(usage()),
+        }
+    }
+    let pattern = CodePattern::parse(pattern)?.with_date_range(from, to);
+
+    // 过滤模式：输出文件中匹配的版本号（每行一个，忽略空行和 # 注释）
+    if let Some(path) = filter {
+        let content = if path == "-" {
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            std::fs::read_to_string(path)
+        }.map_err(|e| SnError::InvalidParameter(format!("无法读取 {}: {}", path, e)))?;
+
+        let matched = content.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter(|line| pattern.matches(line));
+        if count_only {
+            println!("{}", matched.count());
+        } else {
+            matched.for_each(|line| println!("{}", line));
+        }
+        return Ok(());
+    }
+
+    let total = pattern.count();
+    if count_only {
+        println!("{}", total);
+        return Ok(());
+    }
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut out = std::io::stdout().lock();
+    for code in pattern.iter().take(limit) {
+        // 输出端关闭（如管道接 head）时停止枚举
+        if writeln!(out, "{}", code).is_err() {
+            return Ok(());
+        }
+    }
+    if (limit as u64) < total {
+        eprintln!("已显示前 {} 个，共 {} 个", limit, total);
+    }
+    Ok(())
+}
+
+fn firmware_calendar(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: firmware calendar [--year YYYY | --from YYYY-MM-DD --to YYYY-MM-DD] [--format csv|html|ics] [--output <文件>]".to_string()
+    );
+
+    let (mut from, mut to) = (None, None);
+    let mut format = CalendarFormat::Csv;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--year" => {
+                let year = parse_arg(iter.next().ok_or_else(usage)?, "年份")?;
+                from = NaiveDate::from_ymd_opt(year, 1, 1);
+                to = NaiveDate::from_ymd_opt(year, 12, 31);
+            }
+            "--from" => from = Some(parse_date_arg(iter.next().ok_or_else(usage)?)?),
+            "--to" => to = Some(parse_date_arg(iter.next().ok_or_else(usage)?)?),
+            "--format" => format = CalendarFormat::from_name(iter.next().ok_or_else(usage)?)?,
+            "--output" | "-o" => output = Some(*iter.next().ok_or_else(usage)?),
+            _ => return Err(usage()),
+        }
+    }
+
+    // 默认从今天起一年
+    let from = match from {
+        Some(from) => from,
+        None => factory_clock::today(&SystemClock)?,
+    };
+    let to = match to {
+        Some(to) => to,
+        None => from.checked_add_months(Months::new(12))
+            .and_then(|d| d.pred_opt())
+            .ok_or_else(usage)?,
+    };
+
+    let content = date_calendar::render_calendar(&date_calendar::calendar(from, to)?, format);
+    match output {
+        Some(path) => {
+            std::fs::write(path, content)
+                .map_err(|e| SnError::InvalidParameter(format!("无法写入文件 {}: {}", path, e)))?;
+            println!("已导出 {} 至 {} 的日期编码: {}", from, to, path);
+            Ok(())
+        }
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+fn firmware_date_diff(first: &str, second: &str) -> Result<(), SnError> {
+    let diff = date_calendar::date_difference(first, second)?;
+    println!("{}: {}", first.to_ascii_uppercase(), diff.first);
+    println!("{}: {}", second.to_ascii_uppercase(), diff.second);
+    match diff.ordering() {
+        Ordering::Less => println!("{} 早于 {} {} 天", first.to_ascii_uppercase(), second.to_ascii_uppercase(), diff.days),
+        Ordering::Greater => println!("{} 晚于 {} {} 天", first.to_ascii_uppercase(), second.to_ascii_uppercase(), -diff.days),
+        Ordering::Equal => println!("日期相同"),
+    }
+    Ok(())
+}
+
+// 逐字段比较多个版本号（以第一个为基准），不同的字段以 * 标出
+fn firmware_compare(codes: &[&str]) -> Result<(), SnError> {
+    if codes.len() < 2 {
+        return Err(SnError::InvalidParameter("用法: firmware compare <版本号1> <版本号2> [<版本号3> ...]".to_string()));
+    }
+    let comparison = Comparison::new(codes)?;
+    let rows = comparison.rows();
+
+    let label_width = rows.iter().map(|r| display_width(r.field.label())).max().unwrap_or(0) + 2;
+    let column_widths: Vec<usize> = comparison.codes.iter()
+        .enumerate()
+        .map(|(i, c)| rows.iter().map(|r| display_width(&r.values[i])).chain([c.code.len()]).max().unwrap_or(0) + 2)
+        .collect();
+
+    let mut header = pad("  字段", label_width + 2);
+    for (c, &width) in comparison.codes.iter().zip(&column_widths) {
+        header.push_str(&pad(&c.code, width));
+    }
+    println!("{}", header.trim_end());
+    for row in &rows {
+        let marker = if row.differs { "* " } else { "  " };
+        let mut line = pad(&format!("{}{}", marker, row.field.label()), label_width + 2);
+        for (value, &width) in row.values.iter().zip(&column_widths) {
+            line.push_str(&pad(value, width));
+        }
+        println!("{}", line.trim_end());
+    }
+    let mut line = pad("  日期差", label_width + 2);
+    for (c, &width) in comparison.codes.iter().zip(&column_widths) {
+        line.push_str(&pad(&format!("{:+} 天", c.days_from_first), width));
+    }
+    println!("{}", line.trim_end());
+
+    let verdict = comparison.verdict();
+    println!("结论: {}", verdict.label());
+    if let Verdict::Incompatible(differences) = &verdict {
+        for diff in differences {
+            println!("  {}", diff);
+        }
+    }
+    Ok(())
+}
+
+// 终端显示宽度（非 ASCII 字符按两列计）
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+fn firmware_age(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter("用法: firmware age <固件版本号|日期编码> [--today YYYY-MM-DD]".to_string());
+
+    let (code, rest) = args.split_first().ok_or_else(usage)?;
+    let today = match rest {
+        [] => factory_clock::today(&SystemClock)?,
+        ["--today", date] => parse_date_arg(date)?,
+        _ => return Err(usage()),
+    };
+
+    let date = date_calendar::code_date(code)?;
+    println!("{}: {}", code.to_ascii_uppercase(), date);
+    println!("距今: {}", date_calendar::describe_age(date, today));
+    Ok(())
+}
+
+fn catalog_command(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter(
+        "用法: catalog [--catalog <文件>] add <固件版本号> [--file <镜像>] [--status <状态>] [--notes <说明>]\n\
+         \x20     catalog [--catalog <文件>] status <固件版本号> <beta|released|withdrawn>\n\
+         \x20     catalog [--catalog <文件>] query [--pcb N] [--dram 大小|DRAMLess] [--package 代码|名称] [--chips N]\n\
+         \x20                                   [--status 状态] [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--quarter YYYY-Qn] [--latest]".to_string()
+    );
+
+    let (path, args) = match args {
+        ["--catalog", path, rest @ ..] => (*path, rest),
+        _ => (catalog::DEFAULT_CATALOG, args),
+    };
+    let path = std::path::Path::new(path);
+    let mut catalog = Catalog::load(path)?;
+
+    match args {
+        ["add", code, rest @ ..] => {
+            let mut record = CatalogRecord::new(code, ReleaseStatus::Beta)?;
+            let mut iter = rest.iter();
+            while let Some(&arg) = iter.next() {
+                match arg {
+                    "--file" => {
+                        let file = *iter.next().ok_or_else(usage)?;
+                        let data = std::fs::read(file)
+                            .map_err(|e| SnError::InvalidParameter(format!("无法读取 {}: {}", file, e)))?;
+                        record.sha256 = Some(fw_organize::sha256_hex(&data));
+                        record.file = Some(file.to_string());
+                    }
+                    "--status" => record.status = ReleaseStatus::from_name(iter.next().ok_or_else(usage)?)?,
+                    "--notes" => record.notes = iter.next().ok_or_else(usage)?.to_string(),
+                    _ => return Err(usage()),
+                }
+            }
+            let added = format!("已添加 {} ({})", record.code, record.status.label());
+            catalog.add(record)?;
+            catalog.save(path)?;
+            println!("{}", added);
+            Ok(())
+        }
+        ["status", code, status] => {
+            let status = ReleaseStatus::from_name(status)?;
+            catalog.set_status(code, status)?;
+            catalog.save(path)?;
+            println!("{} 已标记为{}", code.to_ascii_uppercase(), status.label());
+            Ok(())
+        }
+        ["query", rest @ ..] | ["list", rest @ ..] => {
+            let mut query = CatalogQuery::default();
+            let mut iter = rest.iter();
+            while let Some(&arg) = iter.next() {
+                let mut value = || iter.next().copied().ok_or_else(usage);
+                match arg {
+                    "--pcb" => query.pcb_size = Some(parse_arg(value()?, "PCB尺寸")?),
+                    "--dram" => query.dram_size_mb = Some(FirmwareCodec::parse_dram_size(value()?)?),
+                    "--package" => {
+                        query.package_code = Some(CONFIG.firmware.find_package(value()?)?);
+                    }
+                    "--chips" => query.chip_count = Some(parse_arg(value()?, "颗粒数")?),
+                    "--status" => query.status = Some(ReleaseStatus::from_name(value()?)?),
+                    "--from" => query.from = Some(parse_date_arg(value()?)?),
+                    "--to" => query.to = Some(parse_date_arg(value()?)?),
+                    "--quarter" => query.set_quarter(value()?)?,
+                    "--latest" => query.latest = true,
+                    _ => return Err(usage()),
+                }
+            }
+
+            let results = catalog.query(&query);
+            for r in &results {
+                let date = r.date().map(|d| d.to_string()).unwrap_or_default();
+                println!(
+                    "{}  {}  {:<9}  {}  {}",
+                    r.code, date, r.status.name(), r.file.as_deref().unwrap_or("-"), r.notes
+                );
+            }
+            println!("共 {} 条", results.len());
+            for r in catalog.invalid_records() {
+                eprintln!("警告: {} 无法按当前配置解析", r.code);
+            }
+            Ok(())
+        }
+        _ => Err(usage()),
+    }
+}
+
+fn serve(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter("用法: serve [--bind 127.0.0.1:PORT] [--allow-remote]".to_string());
+    let mut bind = "127.0.0.1:8080";
+    let mut allow_remote = false;
+    let mut rest = args;
+    while !rest.is_empty() {
+        rest = match rest {
+            ["--bind", addr, tail @ ..] => {
+                bind = addr;
+                tail
+            }
+            ["--allow-remote", tail @ ..] => {
+                allow_remote = true;
+                tail
+            }
+            _ => return Err(usage()),
+        };
+    }
+
+    let server = ApiServer::bind(bind, allow_remote)?;
+    if allow_remote {
+        eprintln!("警告: HTTP 服务没有认证，网络中的其他主机也可以访问");
+    }
+    match server.local_addr() {
+        Some(addr) => println!("HTTP 服务已启动: http://{}", addr),
+        None => println!("HTTP 服务已启动: {}", bind),
+    }
+    server.run();
+    Ok(())
+}
+
+fn codegen(args: &[&str]) -> Result<(), SnError> {
+    let usage = || SnError::InvalidParameter("用法: codegen --lang c|rust|python|json [--output <文件>]".to_string());
+
+    let mut lang = None;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "--lang" => lang = Some(Lang::from_name(iter.next().ok_or_else(usage)?)?),
+            "--output" | "-o" => output = Some(*iter.next().ok_or_else(usage)?),
+            _ => return Err(usage()),
+        }
+    }
+
+    let codebook = Codebook::from_config(&CONFIG.firmware)?;
+    let content = codebook.render(lang.ok_or_else(usage)?);
+    match output {
+        Some(path) => std::fs::write(path, content)
+            .map_err(|e| SnError::InvalidParameter(format!("无法写入文件 {}: {}", path, e))),
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+// 日期参数：YYYY-MM-DD，或 today / now（按工厂时区的当前生产日期）
+fn parse_date_arg(value: &str) -> Result<NaiveDate, SnError> {
+    factory_clock::resolve_date(value, &SystemClock)
+}
+
+fn parse_arg<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, SnError> {
+    value.parse()
+        .map_err(|_| SnError::InvalidParameter(format!("无效的{}: {}", name, value)))
+}
@@ -0,0 +1,177 @@
+use chrono::NaiveDate;
+use crate::code_segments::CodeField;
+use crate::config::{dram_size_label, CONFIG};
+use crate::date_calendar;
+use crate::error::SnError;
+use crate::firmware_codec::{FirmwareCodec, FirmwareFields};
+
+// 参与比较的版本号
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparedCode {
+    pub code: String,
+    pub fields: FirmwareFields,
+    pub date: NaiveDate,
+    // 相对第一个版本号的天数（晚于为正）
+    pub days_from_first: i64,
+}
+
+// 比较结论
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    // 完全相同
+    Identical,
+    // 硬件兼容，只是构建日期不同
+    DateOnly,
+    // 硬件不兼容（各版本号与第一个的差异说明）
+    Incompatible(Vec<String>),
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Identical => "完全相同",
+            Verdict::DateOnly => "硬件兼容，仅构建日期不同",
+            Verdict::Incompatible(_) => "硬件不兼容",
+        }
+    }
+}
+
+// 比较表中的一行（一个字段）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareRow {
+    pub field: CodeField,
+    pub values: Vec<String>,
+    // 各版本号在该字段上不全相同
+    pub differs: bool,
+}
+
+// 多个版本号的逐字段比较（以第一个为基准）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub codes: Vec<ComparedCode>,
+}
+
+impl Comparison {
+    pub fn new<S: AsRef<str>>(codes: &[S]) -> Result<Self, SnError> {
+        if codes.len() < 2 {
+            return Err(SnError::InvalidParameter("至少需要两个固件版本号".to_string()));
+        }
+
+        let first = codes[0].as_ref();
+        let codes = codes.iter()
+            .map(|code| {
+                let code = code.as_ref().trim().to_ascii_uppercase();
+                let fields = FirmwareCodec::parse_firmware_code(&code)
+                    .map_err(|e| SnError::SnFormatError(format!("{}: {}", code, e.message())))?;
+                let diff = date_calendar::date_difference(first, &code)?;
+                Ok(ComparedCode {
+                    code,
+                    fields,
+                    date: diff.second,
+                    days_from_first: diff.days,
+                })
+            })
+            .collect::<Result<Vec<_>, SnError>>()?;
+        Ok(Self { codes })
+    }
+
+    // 逐字段的比较表（不含固定前缀）
+    pub fn rows(&self) -> Vec<CompareRow> {
+        CodeField::ALL.iter()
+            .filter(|&&field| field != CodeField::Prefix)
+            .map(|&field| {
+                let values: Vec<String> = self.codes.iter().map(|c| field_value(field, &c.fields)).collect();
+                let differs = values.iter().any(|v| *v != values[0]);
+                CompareRow { field, values, differs }
+            })
+            .collect()
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        let first = &self.codes[0];
+        let differences: Vec<String> = self.codes[1..].iter()
+            .flat_map(|c| {
+                FirmwareCodec::hardware_differences(&first.fields, &c.fields)
+                    .into_iter()
+                    .map(move |diff| format!("{}: {}", c.code, diff))
+            })
+            .collect();
+
+        if !differences.is_empty() {
+            Verdict::Incompatible(differences)
+        } else if self.codes.iter().all(|c| c.date == first.date) {
+            Verdict::Identical
+        } else {
+            Verdict::DateOnly
+        }
+    }
+}
+
+// 字段的显示值
+fn field_value(field: CodeField, fields: &FirmwareFields) -> String {
+    let (year, month, day, pcb_size, dram_size_mb, package_code, chip_count) = *fields;
+    match field {
+        CodeField::Prefix => String::new(),
+        CodeField::Date => format!("{}-{:02}-{:02}", year, month, day),
+        CodeField::PcbSize => format!("{} ({})", pcb_size, CONFIG.firmware.get_pcb_size_name(pcb_size)),
+        CodeField::DramSize => dram_size_label(dram_size_mb),
+        CodeField::Package => format!("{} ({})", package_code, CONFIG.firmware.get_package_name(package_code)),
+        CodeField::ChipCount => chip_count.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(day: u32, dram_size_mb: i32, package_code: char, chip_count: u8) -> String {
+        FirmwareCodec::generate_firmware_code(2025, 8, day, 1, dram_size_mb, package_code, chip_count).unwrap()
+    }
+
+    #[test]
+    fn identical_codes() {
+        let a = code(1, 1024, 'A', 4);
+        let comparison = Comparison::new(&[a.clone(), a.to_ascii_lowercase()]).unwrap();
+        assert_eq!(comparison.verdict(), Verdict::Identical);
+        assert!(comparison.rows().iter().all(|row| !row.differs));
+        assert_eq!(comparison.codes[1].code, a);
+    }
+
+    #[test]
+    fn date_only_differences() {
+        let comparison = Comparison::new(&[code(10, 1024, 'A', 4), code(1, 1024, 'A', 4), code(12, 1024, 'A', 4)]).unwrap();
+        assert_eq!(comparison.verdict(), Verdict::DateOnly);
+        let days: Vec<i64> = comparison.codes.iter().map(|c| c.days_from_first).collect();
+        assert_eq!(days, [0, -9, 2]);
+
+        let rows = comparison.rows();
+        assert_eq!(rows.len(), CodeField::ALL.len() - 1);
+        let differing: Vec<CodeField> = rows.iter().filter(|row| row.differs).map(|row| row.field).collect();
+        assert_eq!(differing, [CodeField::Date]);
+        assert_eq!(rows[0].values, ["2025-08-10", "2025-08-01", "2025-08-12"]);
+    }
+
+    #[test]
+    fn hardware_differences_are_incompatible() {
+        let first = code(1, 1024, 'A', 4);
+        let second = code(2, 2048, 'A', 4);
+        let third = code(1, 1024, 'B', 8);
+        let comparison = Comparison::new(&[&first, &second, &third]).unwrap();
+        match comparison.verdict() {
+            Verdict::Incompatible(differences) => {
+                assert_eq!(differences.len(), 3);
+                assert!(differences[0].starts_with(&format!("{}: DRAM大小不同", second)));
+                assert!(differences[1].starts_with(&format!("{}: 封装不同", third)));
+                assert!(differences[2].starts_with(&format!("{}: 颗粒数不同", third)));
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_too_few_or_invalid_codes() {
+        let a = code(1, 1024, 'A', 4);
+        assert_eq!(Comparison::new(&[&a]).unwrap_err().kind(), "invalid_parameter");
+        assert_eq!(Comparison::new(&[a.as_str(), "S00019A4"]).unwrap_err().kind(), "format_error");
+    }
+}
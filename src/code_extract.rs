@@ -0,0 +1,174 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+use crate::config::{dram_size_label, CONFIG};
+use crate::firmware_codec::{FirmwareCodec, FirmwareFields};
+use crate::firmware_scan;
+
+// 从文本中提取的版本号
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedCode {
+    // 所在行（从 1 开始）
+    pub line: usize,
+    pub code: String,
+    // 解析结果或错误原因
+    pub fields: Result<FirmwareFields, String>,
+}
+
+impl ExtractedCode {
+    pub fn date(&self) -> String {
+        self.fields.as_ref()
+            .map(|&(year, month, day, ..)| format!("{}-{:02}-{:02}", year, month, day))
+            .unwrap_or_default()
+    }
+
+    pub fn pcb(&self) -> String {
+        self.fields.as_ref()
+            .map(|&(_, _, _, pcb_size, ..)| format!("{} ({})", pcb_size, CONFIG.firmware.get_pcb_size_name(pcb_size)))
+            .unwrap_or_default()
+    }
+
+    pub fn dram(&self) -> String {
+        self.fields.as_ref()
+            .map(|&(_, _, _, _, dram_size_mb, ..)| dram_size_label(dram_size_mb))
+            .unwrap_or_default()
+    }
+
+    pub fn package(&self) -> String {
+        self.fields.as_ref()
+            .map(|&(.., package_code, _)| format!("{} ({})", package_code, CONFIG.firmware.get_package_name(package_code)))
+            .unwrap_or_default()
+    }
+
+    pub fn chips(&self) -> String {
+        self.fields.as_ref()
+            .map(|&(.., chip_count)| chip_count.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn error(&self) -> &str {
+        self.fields.as_ref().err().map(String::as_str).unwrap_or_default()
+    }
+
+    // 是否包含搜索文本（版本号、各字段和错误，大小写不敏感）
+    pub fn contains(&self, text: &str) -> bool {
+        let text = text.trim().to_lowercase();
+        text.is_empty() || [
+            self.code.clone(), self.date(), self.pcb(), self.dram(), self.package(), self.chips(), self.error().to_string(),
+        ].iter().any(|value| value.to_lowercase().contains(&text))
+    }
+}
+
+// 表格的排序列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Line,
+    Code,
+    Date,
+    Pcb,
+    Dram,
+    Package,
+    Chips,
+    Error,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 8] = [
+        SortColumn::Line,
+        SortColumn::Code,
+        SortColumn::Date,
+        SortColumn::Pcb,
+        SortColumn::Dram,
+        SortColumn::Package,
+        SortColumn::Chips,
+        SortColumn::Error,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Line => "行",
+            SortColumn::Code => "版本号",
+            SortColumn::Date => "生产日期",
+            SortColumn::Pcb => "PCB尺寸",
+            SortColumn::Dram => "DRAM大小",
+            SortColumn::Package => "封装类型",
+            SortColumn::Chips => "颗粒数量",
+            SortColumn::Error => "错误",
+        }
+    }
+
+    // 按该列比较（无法解析的行排在有效行之后）
+    pub fn compare(&self, a: &ExtractedCode, b: &ExtractedCode) -> Ordering {
+        let fields = |e: &ExtractedCode| e.fields.as_ref().ok().copied();
+        let by_fields = |key: fn(&FirmwareFields) -> i64| {
+            match (fields(a), fields(b)) {
+                (Some(x), Some(y)) => key(&x).cmp(&key(&y)),
+                (x, y) => y.is_some().cmp(&x.is_some()),
+            }
+        };
+        let ordering = match self {
+            SortColumn::Line => a.line.cmp(&b.line),
+            SortColumn::Code => a.code.cmp(&b.code),
+            SortColumn::Date => by_fields(|f| i64::from(f.0) * 10000 + i64::from(f.1) * 100 + i64::from(f.2)),
+            SortColumn::Pcb => by_fields(|f| i64::from(f.3)),
+            SortColumn::Dram => by_fields(|f| i64::from(f.4)),
+            SortColumn::Package => by_fields(|f| i64::from(u32::from(f.5))),
+            SortColumn::Chips => by_fields(|f| i64::from(f.6)),
+            SortColumn::Error => a.error().cmp(b.error()),
+        };
+        ordering.then_with(|| a.line.cmp(&b.line))
+    }
+}
+
+// 提取文本中所有候选版本号并逐个解析（按出现顺序，保留重复）
+pub fn extract_codes(text: &str) -> Vec<ExtractedCode> {
+    let data = text.as_bytes();
+    // 候选按偏移从小到大排列，行号从上一个候选处接着数
+    let (mut offset, mut line) = (0, 1);
+    firmware_scan::scan_candidates(data)
+        .into_iter()
+        .map(|m| {
+            line += data[offset..m.offset].iter().filter(|&&b| b == b'\n').count();
+            offset = m.offset;
+            ExtractedCode {
+                line,
+                fields: FirmwareCodec::parse_firmware_code(&m.code).map_err(|e| e.message().to_string()),
+                code: m.code,
+            }
+        })
+        .collect()
+}
+
+// 导出为 CSV
+pub fn to_csv<'a>(rows: impl IntoIterator<Item = &'a ExtractedCode>) -> String {
+    let quote = |value: &str| {
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    };
+    let mut out = String::from("line,code,date,pcb,dram,package,chips,error\n");
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            row.line, row.code, row.date(), quote(&row.pcb()), row.dram(), quote(&row.package()), row.chips(), quote(row.error())
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_codes_with_line_numbers() {
+        let text = "版本 S5EJ13B4\n\n返修: S5F013B4, SZZZZZZZ\r\nS5EJ13B4";
+        let rows = extract_codes(text);
+        let found: Vec<(usize, &str)> = rows.iter().map(|r| (r.line, r.code.as_str())).collect();
+        assert_eq!(found, vec![(1, "S5EJ13B4"), (3, "S5F013B4"), (3, "SZZZZZZZ"), (4, "S5EJ13B4")]);
+        assert!(rows[0].fields.is_ok());
+        assert!(rows[2].fields.is_err());
+    }
+}
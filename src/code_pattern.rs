@@ -0,0 +1,345 @@
+use chrono::{Datelike, NaiveDate};
+use ssd_fw_core::{self as core, DATE_CODE_LEN, FIRMWARE_CODE_LEN, FIRMWARE_PREFIX};
+use crate::config::{CodecLimits, CONFIG};
+use crate::error::SnError;
+use crate::firmware_codec::CodebookTables;
+
+// 日期编码所在位置
+const DATE_START: usize = 1;
+const DATE_END: usize = 4;
+
+// 固件版本号匹配模式
+//
+// 每个位置可以是具体字符、`?`（该位置的任意有效字符）或字符类，如 `[0-3A]`、`[!X]`。
+// 例如 `S???1XA?` 匹配 PCB 1、DRAMLess、BGA100 的所有版本号。
+#[derive(Debug, Clone)]
+pub struct CodePattern {
+    // 每个位置允许的字符（已与编码表取交集，按字符顺序排列）
+    allowed: [Vec<u8>; FIRMWARE_CODE_LEN],
+    // 当前纪元的编码表
+    tables: &'static CodebookTables,
+    // 日期范围（自 1970-01-01 起的天数，闭区间）
+    first_day: i64,
+    last_day: i64,
+}
+
+// 模式中单个位置的写法
+enum Token {
+    Any,
+    Literal(u8),
+    Class { negated: bool, ranges: Vec<(u8, u8)> },
+}
+
+impl Token {
+    fn accepts(&self, c: u8) -> bool {
+        match self {
+            Token::Any => true,
+            Token::Literal(l) => *l == c,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&c)) != *negated
+            }
+        }
+    }
+}
+
+impl CodePattern {
+    pub fn parse(pattern: &str) -> Result<Self, SnError> {
+        let tokens = tokenize(pattern)?;
+        let limits = CONFIG.firmware.limits()?;
+        let valid = valid_chars(&limits);
+
+        let allowed: [Vec<u8>; FIRMWARE_CODE_LEN] = std::array::from_fn(|i| {
+            valid[i].iter().copied().filter(|&c| tokens[i].accepts(c)).collect()
+        });
+
+        let tables = CONFIG.tables(limits.era)?;
+        let (first_day, last_day) = (tables.codebook().first_day(), tables.codebook().last_day());
+        Ok(Self {
+            allowed,
+            tables,
+            first_day,
+            last_day,
+        })
+    }
+
+    // 限制日期范围（闭区间，超出编码范围的部分自动截去）
+    pub fn with_date_range(mut self, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        let days = |d: NaiveDate| core::days_from_civil(d.year(), d.month(), d.day());
+        if let Some(from) = from {
+            self.first_day = self.first_day.max(days(from));
+        }
+        if let Some(to) = to {
+            self.last_day = self.last_day.min(days(to));
+        }
+        self
+    }
+
+    // 判断版本号是否匹配（大小写不敏感）
+    pub fn matches(&self, code: &str) -> bool {
+        let code = code.trim().as_bytes();
+        if code.len() != FIRMWARE_CODE_LEN {
+            return false;
+        }
+        let all_allowed = code.iter()
+            .zip(&self.allowed)
+            .all(|(c, allowed)| allowed.contains(&c.to_ascii_uppercase()));
+        if !all_allowed {
+            return false;
+        }
+
+        // 按周编码时一个编码覆盖多天，只要与日期范围有交集即匹配
+        let Ok(date) = self.tables.codebook().code_to_date(&code[DATE_START..DATE_END]) else {
+            return false;
+        };
+        let start = core::days_from_civil(date.year, date.month, date.day);
+        let date_code = self.date_code(start);
+        let mut end = start;
+        while date_code.is_some() && self.date_code(end + 1) == date_code {
+            end += 1;
+        }
+        start <= self.last_day && end >= self.first_day
+    }
+
+    // 匹配的版本号数量（不生成版本号）
+    pub fn count(&self) -> u64 {
+        let days = (self.first_day..=self.last_day)
+            .filter(|&day| self.day_matches(day))
+            .count() as u64;
+        self.allowed[DATE_END..].iter().fold(days, |acc, chars| acc * chars.len() as u64)
+    }
+
+    // 按日期从早到晚依次生成匹配的版本号
+    pub fn iter(&self) -> Matches<'_> {
+        let mut matches = Matches {
+            pattern: self,
+            day: self.first_day,
+            indices: [0; FIRMWARE_CODE_LEN - DATE_END],
+            done: self.allowed.iter().any(|chars| chars.is_empty()),
+        };
+        if !matches.done {
+            matches.seek_day();
+        }
+        matches
+    }
+
+    fn date_code(&self, day: i64) -> Option<[u8; DATE_CODE_LEN]> {
+        self.tables.codebook().date_to_code(core::civil_from_days(day)).ok()
+    }
+
+    // 日期是否为某个日期编码在范围内的第一天，且编码与模式匹配
+    fn day_matches(&self, day: i64) -> bool {
+        let Some(code) = self.date_code(day) else {
+            return false;
+        };
+        let first_of_code = day == self.first_day || self.date_code(day - 1) != Some(code);
+        first_of_code && code.iter()
+            .zip(&self.allowed[DATE_START..DATE_END])
+            .all(|(c, allowed)| allowed.contains(c))
+    }
+}
+
+// 匹配版本号的惰性迭代器
+pub struct Matches<'a> {
+    pattern: &'a CodePattern,
+    day: i64,
+    // 硬件字段（PCB/DRAM/封装/颗粒数）在各自允许字符中的下标
+    indices: [usize; FIRMWARE_CODE_LEN - DATE_END],
+    done: bool,
+}
+
+impl Matches<'_> {
+    // 前进到下一个匹配的日期（包括当前日期）
+    fn seek_day(&mut self) {
+        while self.day <= self.pattern.last_day && !self.pattern.day_matches(self.day) {
+            self.day += 1;
+        }
+        if self.day > self.pattern.last_day {
+            self.done = true;
+        }
+    }
+}
+
+impl Iterator for Matches<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+
+        let allowed = &self.pattern.allowed;
+        let mut code = [0u8; FIRMWARE_CODE_LEN];
+        code[0] = allowed[0][0];
+        code[DATE_START..DATE_END].copy_from_slice(&self.pattern.date_code(self.day)?);
+        for (i, &index) in self.indices.iter().enumerate() {
+            code[DATE_END + i] = allowed[DATE_END + i][index];
+        }
+
+        // 硬件字段按进位方式递增，全部进位后换到下一个日期
+        let mut carried = true;
+        for i in (0..self.indices.len()).rev() {
+            self.indices[i] += 1;
+            if self.indices[i] < allowed[DATE_END + i].len() {
+                carried = false;
+                break;
+            }
+            self.indices[i] = 0;
+        }
+        if carried {
+            self.day += 1;
+            self.seek_day();
+        }
+
+        Some(code.iter().map(|&c| c as char).collect())
+    }
+}
+
+// 各位置在当前编码表下的有效字符
+fn valid_chars(limits: &CodecLimits) -> [Vec<u8>; FIRMWARE_CODE_LEN] {
+    let bytes = |chars: &[char]| -> Vec<u8> {
+        let mut bytes: Vec<u8> = chars.iter().map(|&c| c as u8).collect();
+        bytes.sort_unstable();
+        bytes
+    };
+    let alphabet: Vec<u8> = limits.date_chars.iter().map(|&c| c as u8).collect();
+
+    [
+        vec![FIRMWARE_PREFIX],
+        alphabet.clone(),
+        alphabet.clone(),
+        alphabet,
+        bytes(&limits.pcb_codes),
+        bytes(&limits.dram_codes),
+        bytes(&limits.packages),
+        bytes(&limits.chip_codes),
+    ]
+}
+
+fn tokenize(pattern: &str) -> Result<Vec<Token>, SnError> {
+    let err = |msg: &str| SnError::InvalidParameter(format!("无效的匹配模式 \"{}\": {}", pattern, msg));
+    let upper = pattern.trim().to_ascii_uppercase();
+    if !upper.is_ascii() {
+        return Err(err("只能包含 ASCII 字符"));
+    }
+
+    let mut tokens = Vec::new();
+    let mut bytes = upper.bytes();
+    while let Some(c) = bytes.next() {
+        let token = match c {
+            b'?' => Token::Any,
+            b'[' => {
+                let mut class = Vec::new();
+                loop {
+                    match bytes.next() {
+                        Some(b']') => break,
+                        Some(c) => class.push(c),
+                        None => return Err(err("字符类缺少 ']'")),
+                    }
+                }
+                let negated = matches!(class.first(), Some(b'!' | b'^'));
+                let items = if negated { &class[1..] } else { &class[..] };
+                if items.is_empty() {
+                    return Err(err("字符类为空"));
+                }
+
+                let mut ranges = Vec::new();
+                let mut i = 0;
+                while i < items.len() {
+                    if i + 2 < items.len() && items[i + 1] == b'-' {
+                        let (lo, hi) = (items[i], items[i + 2]);
+                        if lo > hi {
+                            return Err(err(&format!("字符范围 {}-{} 无效", lo as char, hi as char)));
+                        }
+                        ranges.push((lo, hi));
+                        i += 3;
+                    } else {
+                        ranges.push((items[i], items[i]));
+                        i += 1;
+                    }
+                }
+                Token::Class { negated, ranges }
+            }
+            b']' => return Err(err("多余的 ']'")),
+            c => Token::Literal(c),
+        };
+        tokens.push(token);
+    }
+
+    if tokens.len() != FIRMWARE_CODE_LEN {
+        return Err(err(&format!("应为 {} 个位置，实际为 {} 个", FIRMWARE_CODE_LEN, tokens.len())));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firmware_codec::FirmwareCodec;
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn tokenizes_wildcards_and_classes() {
+        let tokens = tokenize("s?[c-e]?1[!x]a?").unwrap();
+        assert_eq!(tokens.len(), FIRMWARE_CODE_LEN);
+        assert!(tokens[0].accepts(b'S') && !tokens[0].accepts(b'T'));
+        assert!(tokens[1].accepts(b'0') && tokens[1].accepts(b'Z'));
+        assert!(tokens[2].accepts(b'C') && tokens[2].accepts(b'E'));
+        assert!(!tokens[2].accepts(b'B') && !tokens[2].accepts(b'F'));
+        assert!(tokens[5].accepts(b'A') && !tokens[5].accepts(b'X'));
+
+        let class = tokenize("S[^0-2A]??????").unwrap();
+        assert!(class[1].accepts(b'3') && !class[1].accepts(b'1') && !class[1].accepts(b'A'));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        for pattern in ["S[12??????", "S[]??????", "S[!]??????", "S[E-C]??????", "S?]?????", "S???", "S???????0", "S??????é"] {
+            assert!(tokenize(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn matches_only_codes_allowed_at_every_position() {
+        let code = FirmwareCodec::generate_firmware_code(2025, 3, 1, 1, -1, 'A', 4).unwrap();
+        let pattern = CodePattern::parse("S???1XA?").unwrap();
+        assert!(pattern.matches(&code));
+        assert!(pattern.matches(&code.to_ascii_lowercase()));
+
+        let other = FirmwareCodec::generate_firmware_code(2025, 3, 1, 1, 1024, 'A', 4).unwrap();
+        assert!(!pattern.matches(&other));
+        assert!(!pattern.matches("S???1XA"));
+        assert!(CodePattern::parse(&code).unwrap().matches(&code));
+    }
+
+    #[test]
+    fn count_agrees_with_iteration() {
+        let pattern = CodePattern::parse("S???1XA?").unwrap().with_date_range(date(2025, 1, 1), date(2025, 1, 10));
+        let chips = CONFIG.firmware.limits().unwrap().chip_codes.len() as u64;
+        assert_eq!(pattern.count(), 10 * chips);
+        assert_eq!(pattern.count(), pattern.iter().count() as u64);
+        assert!(pattern.iter().all(|code| pattern.matches(&code)));
+
+        let narrow = CodePattern::parse("S??[0-3][1-2][!X]A1").unwrap().with_date_range(date(2025, 1, 1), date(2025, 3, 1));
+        assert_eq!(narrow.count(), narrow.iter().count() as u64);
+    }
+
+    #[test]
+    fn date_range_is_clipped_to_encodable_dates() {
+        let limits = CONFIG.firmware.limits().unwrap();
+        let pattern = CodePattern::parse("S???1XA1").unwrap().with_date_range(date(1990, 1, 1), None);
+        let first = pattern.iter().next().unwrap();
+        let (year, month, day, ..) = FirmwareCodec::parse_firmware_code(&first).unwrap();
+        assert_eq!(NaiveDate::from_ymd_opt(year, month, day), Some(limits.first_date));
+
+        let past_end = limits.last_date.succ_opt().unwrap();
+        let tail = CodePattern::parse("S???1XA1").unwrap().with_date_range(Some(limits.last_date), Some(past_end + chrono::Days::new(30)));
+        assert_eq!(tail.count(), 1);
+
+        let empty = CodePattern::parse("S???1XA1").unwrap().with_date_range(date(2025, 2, 1), date(2025, 1, 1));
+        assert_eq!(empty.count(), 0);
+        assert_eq!(empty.iter().next(), None);
+    }
+}
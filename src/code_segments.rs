@@ -0,0 +1,219 @@
+use std::ops::Range;
+use ssd_fw_core::{DATE_CODE_LEN, FIRMWARE_CODE_LEN, FIRMWARE_PREFIX};
+use crate::config::{dram_size_label, CodecLimits, CONFIG};
+use crate::firmware_codec::FirmwareCodec;
+
+// 固件版本号中的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeField {
+    Prefix,
+    Date,
+    PcbSize,
+    DramSize,
+    Package,
+    ChipCount,
+}
+
+impl CodeField {
+    pub const ALL: [CodeField; 6] = [
+        CodeField::Prefix,
+        CodeField::Date,
+        CodeField::PcbSize,
+        CodeField::DramSize,
+        CodeField::Package,
+        CodeField::ChipCount,
+    ];
+
+    // 字段在版本号中的位置
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            CodeField::Prefix => 0..1,
+            CodeField::Date => 1..1 + DATE_CODE_LEN,
+            CodeField::PcbSize => 4..5,
+            CodeField::DramSize => 5..6,
+            CodeField::Package => 6..7,
+            CodeField::ChipCount => 7..FIRMWARE_CODE_LEN,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CodeField::Prefix => "前缀",
+            CodeField::Date => "生产日期",
+            CodeField::PcbSize => "PCB尺寸",
+            CodeField::DramSize => "DRAM大小",
+            CodeField::Package => "封装类型",
+            CodeField::ChipCount => "颗粒数量",
+        }
+    }
+
+    // 该位置的全部有效代码及含义（日期编码只列出编码方式与范围）
+    pub fn code_table(&self, limits: &CodecLimits) -> Vec<(String, String)> {
+        match self {
+            CodeField::Prefix => vec![((FIRMWARE_PREFIX as char).to_string(), "固件版本号前缀".to_string())],
+            CodeField::Date => vec![
+                ("编码方式".to_string(), CONFIG.firmware.date_encoding.label().to_string()),
+                ("字符集".to_string(), limits.date_chars.iter().collect()),
+                ("可编码日期".to_string(), limits.date_range_desc()),
+                ("纪元".to_string(), limits.era.to_string()),
+            ],
+            CodeField::PcbSize => limits.pcb_codes.iter()
+                .zip(&limits.pcb_sizes)
+                .map(|(code, &size)| (
+                    code.to_string(),
+                    retired_note(format!("{} - {}", size, CONFIG.firmware.get_pcb_size_name(size)), CONFIG.firmware.is_retired_pcb_size(size)),
+                ))
+                .collect(),
+            CodeField::DramSize => limits.dram_codes.iter()
+                .map(|&code| (code.to_string(), retired_note(CONFIG.firmware.get_dram_size_desc(code), CONFIG.firmware.is_retired_dram_code(code))))
+                .collect(),
+            CodeField::Package => limits.packages.iter()
+                .map(|&code| (code.to_string(), retired_note(CONFIG.firmware.get_package_name(code), CONFIG.firmware.is_retired_package(code))))
+                .collect(),
+            CodeField::ChipCount => limits.chip_codes.iter()
+                .zip(limits.chip_range())
+                .map(|(code, count)| (code.to_string(), format!("{} 颗", count)))
+                .collect(),
+        }
+    }
+}
+
+// 字段的解码状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentState {
+    // 尚未输入完整
+    Incomplete,
+    // 含义
+    Valid(String),
+    // 错误原因
+    Invalid(String),
+}
+
+// 版本号中一个字段的解码结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSegment {
+    pub field: CodeField,
+    // 已输入的字符（可能不足该字段的长度）
+    pub text: String,
+    pub state: SegmentState,
+}
+
+// 逐字段解码（可为输入到一半的版本号，已输入完整的字段各自解码）
+pub fn decode_segments(code: &str) -> Vec<CodeSegment> {
+    let chars: Vec<char> = code.trim().chars().map(|c| c.to_ascii_uppercase()).collect();
+    CodeField::ALL.iter()
+        .map(|&field| {
+            let range = field.range();
+            let text: String = chars.iter().skip(range.start).take(range.len()).collect();
+            let state = if text.chars().count() < range.len() {
+                SegmentState::Incomplete
+            } else {
+                decode_field(field, &text)
+            };
+            CodeSegment { field, text, state }
+        })
+        .collect()
+}
+
+// 已停用的代码在含义后注明
+fn retired_note(meaning: String, retired: bool) -> String {
+    if retired {
+        format!("{} (已停用)", meaning)
+    } else {
+        meaning
+    }
+}
+
+fn decode_field(field: CodeField, text: &str) -> SegmentState {
+    let c = text.chars().next().unwrap_or_default();
+    let result = match field {
+        CodeField::Prefix if c == FIRMWARE_PREFIX as char => Ok("固件版本号".to_string()),
+        CodeField::Prefix => Err(format!("前缀应为 {}", FIRMWARE_PREFIX as char)),
+        CodeField::Date => FirmwareCodec::code_to_date(text)
+            .map(|(year, month, day)| format!("{}-{:02}-{:02}", year, month, day))
+            .map_err(|e| e.message().to_string()),
+        CodeField::PcbSize => FirmwareCodec::char_to_pcb_size(c)
+            .map(|size| retired_note(
+                format!("{} ({})", size, CONFIG.firmware.get_pcb_size_name(size)),
+                CONFIG.firmware.is_retired_pcb_size(size),
+            ))
+            .map_err(|e| e.message().to_string()),
+        CodeField::DramSize => FirmwareCodec::dram_code_to_size(c)
+            .map(|size| retired_note(dram_size_label(size), CONFIG.firmware.is_retired_dram_code(c)))
+            .map_err(|e| e.message().to_string()),
+        CodeField::Package if CONFIG.firmware.is_valid_package(c) => {
+            Ok(retired_note(CONFIG.firmware.get_package_name(c), CONFIG.firmware.is_retired_package(c)))
+        }
+        CodeField::Package => Err("无效的封装代码".to_string()),
+        CodeField::ChipCount => match FirmwareCodec::char_to_chip_count(c) {
+            Ok(count) if CONFIG.firmware.is_valid_chip_count(count) => Ok(format!("{} 颗", count)),
+            Ok(_) => Err("颗粒数量超出配置范围".to_string()),
+            Err(e) => Err(e.message().to_string()),
+        },
+    };
+    match result {
+        Ok(meaning) => SegmentState::Valid(meaning),
+        Err(error) => SegmentState::Invalid(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code() -> String {
+        FirmwareCodec::generate_firmware_code(2025, 8, 1, 1, 1024, 'A', 4).unwrap()
+    }
+
+    fn decode_states(code: &str) -> Vec<SegmentState> {
+        decode_segments(code).into_iter().map(|s| s.state).collect()
+    }
+
+    #[test]
+    fn full_code_decodes_every_field() {
+        let code = code();
+        let segments = decode_segments(&format!(" {} ", code.to_ascii_lowercase()));
+        assert_eq!(segments.len(), CodeField::ALL.len());
+        for segment in &segments {
+            assert_eq!(segment.text, code[segment.field.range()], "{:?}", segment.field);
+        }
+        assert_eq!(segments[1].state, SegmentState::Valid("2025-08-01".to_string()));
+        assert_eq!(segments[3].state, SegmentState::Valid(dram_size_label(1024)));
+        assert_eq!(segments[5].state, SegmentState::Valid("4 颗".to_string()));
+    }
+
+    #[test]
+    fn partial_code_leaves_remaining_fields_incomplete() {
+        let code = code();
+        let segments = decode_segments(&code[..3]);
+        assert!(matches!(segments[0].state, SegmentState::Valid(_)));
+        assert_eq!(segments[1].text, code[1..3]);
+        assert!(segments[1..].iter().all(|s| s.state == SegmentState::Incomplete));
+        assert!(segments[2..].iter().all(|s| s.text.is_empty()));
+
+        let states = decode_states(&code[..5]);
+        assert!(matches!(states[2], SegmentState::Valid(_)));
+        assert_eq!(states[3..], [SegmentState::Incomplete, SegmentState::Incomplete, SegmentState::Incomplete]);
+
+        assert!(decode_states("").iter().all(|s| *s == SegmentState::Incomplete));
+    }
+
+    #[test]
+    fn invalid_fields_are_reported_independently() {
+        let code = code();
+        // 前缀、PCB尺寸与封装无效，其余字段照常解码
+        let bad = format!("X{}9{}E{}", &code[1..4], &code[5..6], &code[7..]);
+        let states = decode_states(&bad);
+        assert!(matches!(states[0], SegmentState::Invalid(_)));
+        assert!(matches!(states[1], SegmentState::Valid(_)));
+        assert!(matches!(states[2], SegmentState::Invalid(_)));
+        assert!(matches!(states[3], SegmentState::Valid(_)));
+        assert_eq!(states[4], SegmentState::Invalid("无效的封装代码".to_string()));
+        assert!(matches!(states[5], SegmentState::Valid(_)));
+
+        let states = decode_states(&format!("S{}9AH", &code[1..5]));
+        assert!(matches!(states[3], SegmentState::Invalid(_)));
+        assert!(matches!(states[5], SegmentState::Invalid(_)));
+        assert!(matches!(decode_states("SZZZ")[1], SegmentState::Invalid(_)));
+    }
+}
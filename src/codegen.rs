@@ -0,0 +1,300 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use chrono::Datelike;
+use crate::config::{DateEncoding, FirmwareConfig};
+use crate::error::SnError;
+
+// 输出语言
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lang {
+    C,
+    Rust,
+    Python,
+    Json,
+}
+
+impl Lang {
+    pub fn from_name(name: &str) -> Result<Self, SnError> {
+        match name.to_ascii_lowercase().as_str() {
+            "c" => Ok(Lang::C),
+            "rust" | "rs" => Ok(Lang::Rust),
+            "python" | "py" => Ok(Lang::Python),
+            "json" => Ok(Lang::Json),
+            _ => Err(SnError::InvalidParameter(format!("不支持的输出语言: {} (可选 c|rust|python|json)", name))),
+        }
+    }
+}
+
+// 编码表条目
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeEntry {
+    pub code: char,
+    pub value: i32,
+    pub description: String,
+}
+
+// 完整编码表（按代码排序）
+#[derive(Debug, Clone, Serialize)]
+pub struct Codebook {
+    // 当前纪元的基准日期
+    pub base_date: (i32, u32, u32),
+    pub alphabet: String,
+    // 默认值不参与序列化，保持已有编码表的哈希不变
+    #[serde(skip_serializing_if = "is_day_count")]
+    pub date_encoding: DateEncoding,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub era: usize,
+    pub pcb_sizes: Vec<CodeEntry>,
+    pub dram_sizes: Vec<CodeEntry>,
+    pub packages: Vec<CodeEntry>,
+    pub chip_counts: Vec<CodeEntry>,
+    pub format: String,
+}
+
+impl Codebook {
+    pub fn from_config(config: &FirmwareConfig) -> Result<Self, SnError> {
+        let mut pcb_sizes: Vec<CodeEntry> = config.pcb_sizes.iter()
+            .map(|(&size, desc)| Ok(CodeEntry {
+                code: config.pcb_size_code(size)?,
+                value: i32::from(size),
+                description: desc.clone(),
+            }))
+            .collect::<Result<Vec<_>, SnError>>()?;
+        pcb_sizes.sort_by_key(|e| e.code);
+
+        let mut dram_sizes: Vec<CodeEntry> = config.dram_sizes.iter()
+            .map(|(&code, &size)| CodeEntry {
+                code: code.to_ascii_uppercase(),
+                value: size,
+                description: config.get_dram_size_desc(code),
+            })
+            .collect();
+        dram_sizes.sort_by_key(|e| e.code);
+
+        let mut packages: Vec<CodeEntry> = config.packages.iter()
+            .map(|(&code, desc)| CodeEntry {
+                code: code.to_ascii_uppercase(),
+                value: code.to_ascii_uppercase() as i32,
+                description: desc.clone(),
+            })
+            .collect();
+        packages.sort_by_key(|e| e.code);
+
+        let chip_counts = (config.chip_count.min..=config.chip_count.max)
+            .map(|count| Ok(CodeEntry {
+                code: config.chip_count.count_to_code(count)
+                    .ok_or_else(|| SnError::ConfigError(format!("颗粒数量 {} 没有对应的代码", count)))?,
+                value: i32::from(count),
+                description: format!("{}颗", count),
+            }))
+            .collect::<Result<Vec<_>, SnError>>()?;
+
+        let base_date = config.era_base_date(config.era)?;
+        Ok(Self {
+            base_date: (base_date.year(), base_date.month(), base_date.day()),
+            alphabet: config.base32_chars.clone(),
+            date_encoding: config.date_encoding,
+            era: config.era,
+            pcb_sizes,
+            dram_sizes,
+            packages,
+            chip_counts,
+            format: config.format.clone(),
+        })
+    }
+
+    // 编码表内容哈希（SHA-256，十六进制），与输出语言无关
+    pub fn content_hash(&self) -> String {
+        let canonical = serde_json::to_string(self).unwrap_or_default();
+        Sha256::digest(canonical.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    // 哈希前 32 位，便于固件中以整数比较
+    pub fn content_hash32(&self) -> u32 {
+        u32::from_str_radix(&self.content_hash()[..8], 16).unwrap_or(0)
+    }
+
+    pub fn render(&self, lang: Lang) -> String {
+        match lang {
+            Lang::C => self.render_c(),
+            Lang::Rust => self.render_rust(),
+            Lang::Python => self.render_python(),
+            Lang::Json => self.render_json(),
+        }
+    }
+
+    fn chip_codes(&self) -> String {
+        self.chip_counts.iter().map(|e| e.code).collect()
+    }
+
+    fn render_c(&self) -> String {
+        let mut out = String::new();
+        out.push_str("/* 由 ssd_sn_tool codegen 生成，请勿手动修改 */\n");
+        out.push_str("#ifndef SSD_FW_CODEBOOK_H\n#define SSD_FW_CODEBOOK_H\n\n");
+        out.push_str("#include <stdint.h>\n\n");
+        out.push_str(&format!("#define SSD_FW_CONFIG_HASH \"{}\"\n", self.content_hash()));
+        out.push_str(&format!("#define SSD_FW_CONFIG_HASH32 0x{:08x}u\n\n", self.content_hash32()));
+        out.push_str(&format!("#define SSD_FW_BASE_YEAR {}\n", self.base_date.0));
+        out.push_str(&format!("#define SSD_FW_BASE_MONTH {}\n", self.base_date.1));
+        out.push_str(&format!("#define SSD_FW_BASE_DAY {}\n\n", self.base_date.2));
+        out.push_str(&format!("#define SSD_FW_ALPHABET \"{}\"\n", c_escape(&self.alphabet)));
+        out.push_str(&format!("#define SSD_FW_ALPHABET_LEN {}\n", self.alphabet.len()));
+        out.push_str(&format!("#define SSD_FW_DATE_ENCODING \"{}\"\n", self.date_encoding.name()));
+        out.push_str(&format!("#define SSD_FW_ERA {}\n\n", self.era));
+
+        out.push_str("typedef struct {\n    char code;\n    int32_t value;\n    const char *description;\n} ssd_fw_entry_t;\n\n");
+
+        for (name, prefix, entries) in [
+            ("PCB_SIZES", "PCB", &self.pcb_sizes),
+            ("DRAM_SIZES", "DRAM", &self.dram_sizes),
+            ("PACKAGES", "PKG", &self.packages),
+        ] {
+            for e in entries {
+                out.push_str(&format!(
+                    "#define SSD_FW_{}_{} '{}' /* {} */\n",
+                    prefix, e.code, e.code, e.description.replace("*/", "* /")
+                ));
+            }
+            out.push_str(&format!("#define SSD_FW_{}_COUNT {}\n", name, entries.len()));
+            out.push_str(&format!("static const ssd_fw_entry_t SSD_FW_{}[] = {{\n", name));
+            for e in entries {
+                out.push_str(&format!(
+                    "    {{'{}', {}, \"{}\"}},\n",
+                    e.code, e.value, c_escape(&e.description)
+                ));
+            }
+            out.push_str("};\n\n");
+        }
+
+        let (min, max) = self.chip_range();
+        out.push_str(&format!("#define SSD_FW_CHIP_MIN {}\n", min));
+        out.push_str(&format!("#define SSD_FW_CHIP_MAX {}\n", max));
+        out.push_str("/* 下标为 颗粒数 - SSD_FW_CHIP_MIN */\n");
+        out.push_str(&format!("#define SSD_FW_CHIP_CODES \"{}\"\n\n", c_escape(&self.chip_codes())));
+
+        out.push_str("#endif /* SSD_FW_CODEBOOK_H */\n");
+        out
+    }
+
+    fn render_rust(&self) -> String {
+        let mut out = String::new();
+        out.push_str("// 由 ssd_sn_tool codegen 生成，请勿手动修改\n\n");
+        out.push_str(&format!("pub const CONFIG_HASH: &str = \"{}\";\n", self.content_hash()));
+        out.push_str(&format!("pub const CONFIG_HASH32: u32 = 0x{:08x};\n\n", self.content_hash32()));
+        out.push_str(&format!(
+            "pub const BASE_DATE: (i32, u32, u32) = ({}, {}, {});\n",
+            self.base_date.0, self.base_date.1, self.base_date.2
+        ));
+        out.push_str(&format!("pub const ALPHABET: &[u8] = b{:?};\n", self.alphabet));
+        out.push_str(&format!("pub const DATE_ENCODING: &str = {:?};\n", self.date_encoding.name()));
+        out.push_str(&format!("pub const ERA: usize = {};\n\n", self.era));
+
+        for (name, entries) in [
+            ("PCB_SIZES", &self.pcb_sizes),
+            ("DRAM_SIZES", &self.dram_sizes),
+            ("PACKAGES", &self.packages),
+        ] {
+            out.push_str(&format!("// (代码, 值, 描述)\npub const {}: &[(u8, i32, &str)] = &[\n", name));
+            for e in entries {
+                out.push_str(&format!("    (b'{}', {}, {:?}),\n", e.code, e.value, e.description));
+            }
+            out.push_str("];\n\n");
+        }
+
+        let (min, max) = self.chip_range();
+        out.push_str(&format!("pub const CHIP_MIN: u8 = {};\n", min));
+        out.push_str(&format!("pub const CHIP_MAX: u8 = {};\n", max));
+        out.push_str("// 下标为 颗粒数 - CHIP_MIN\n");
+        out.push_str(&format!("pub const CHIP_CODES: &[u8] = b{:?};\n", self.chip_codes()));
+        out
+    }
+
+    fn render_python(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# 由 ssd_sn_tool codegen 生成，请勿手动修改\n\n");
+        out.push_str(&format!("CONFIG_HASH = \"{}\"\n", self.content_hash()));
+        out.push_str(&format!("CONFIG_HASH32 = 0x{:08x}\n\n", self.content_hash32()));
+        out.push_str(&format!(
+            "BASE_DATE = ({}, {}, {})\n",
+            self.base_date.0, self.base_date.1, self.base_date.2
+        ));
+        out.push_str(&format!("ALPHABET = {}\n", py_str(&self.alphabet)));
+        out.push_str(&format!("DATE_ENCODING = {}\n", py_str(self.date_encoding.name())));
+        out.push_str(&format!("ERA = {}\n\n", self.era));
+
+        for (name, entries) in [
+            ("PCB_SIZES", &self.pcb_sizes),
+            ("DRAM_SIZES", &self.dram_sizes),
+            ("PACKAGES", &self.packages),
+        ] {
+            out.push_str(&format!("# 代码: (值, 描述)\n{} = {{\n", name));
+            for e in entries {
+                out.push_str(&format!(
+                    "    {}: ({}, {}),\n",
+                    py_str(&e.code.to_string()), e.value, py_str(&e.description)
+                ));
+            }
+            out.push_str("}\n\n");
+        }
+
+        let (min, max) = self.chip_range();
+        out.push_str(&format!("CHIP_MIN = {}\n", min));
+        out.push_str(&format!("CHIP_MAX = {}\n", max));
+        out.push_str("# 颗粒数: 代码\nCHIP_CODES = {\n");
+        for e in &self.chip_counts {
+            out.push_str(&format!("    {}: {},\n", e.value, py_str(&e.code.to_string())));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn render_json(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("config_hash".to_string(), self.content_hash().into());
+            obj.insert("config_hash32".to_string(), self.content_hash32().into());
+        }
+        serde_json::to_string_pretty(&value).unwrap_or_default() + "\n"
+    }
+
+    fn chip_range(&self) -> (i32, i32) {
+        let min = self.chip_counts.first().map(|e| e.value).unwrap_or(0);
+        let max = self.chip_counts.last().map(|e| e.value).unwrap_or(0);
+        (min, max)
+    }
+}
+
+fn is_day_count(encoding: &DateEncoding) -> bool {
+    *encoding == DateEncoding::DayCount
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+fn c_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn py_str(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chip_codes_come_from_given_config() {
+        let mut config = FirmwareConfig::default();
+        config.chip_count.codes = "123456789ABCDEFGHIJKLMNOPQRSTUVW".to_string();
+        config.chip_count.max = 32;
+        let codebook = Codebook::from_config(&config).unwrap();
+        assert_eq!(codebook.chip_counts.len(), 32);
+        assert_eq!(codebook.chip_counts.last().map(|e| e.code), Some('W'));
+        assert_eq!(codebook.chip_codes(), config.chip_count.codes);
+    }
+}
@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use chrono::{Datelike, FixedOffset, NaiveDate};
 use lazy_static::lazy_static;
+use ssd_fw_core as core;
 use crate::error::SnError;
+use crate::firmware_codec::CodebookTables;
 
 // 基础日期配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,36 +17,249 @@ pub struct BaseDate {
     pub day: u32,
 }
 
+// 日期编码方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateEncoding {
+    // 自基准日期起的天数
+    #[default]
+    DayCount,
+    // ISO 年 + 周
+    IsoWeek,
+    // 年 + 年内第几天
+    YearDay,
+}
+
+impl DateEncoding {
+    pub fn to_core(self) -> core::DateEncoding {
+        match self {
+            DateEncoding::DayCount => core::DateEncoding::DayCount,
+            DateEncoding::IsoWeek => core::DateEncoding::IsoWeek,
+            DateEncoding::YearDay => core::DateEncoding::YearDay,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DateEncoding::DayCount => "day_count",
+            DateEncoding::IsoWeek => "iso_week",
+            DateEncoding::YearDay => "year_day",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateEncoding::DayCount => "天数",
+            DateEncoding::IsoWeek => "年 + ISO 周",
+            DateEncoding::YearDay => "年 + 年内天数",
+        }
+    }
+}
+
 // 芯片数量范围
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChipCount {
     pub min: u8,
     pub max: u8,
+    // 颗粒数量代码字符集：第 n 个字符表示 n+1 个颗粒
+    #[serde(default = "ChipCount::default_codes")]
+    pub codes: String,
+}
+
+impl ChipCount {
+    // 默认 1-9、A-F、G=16
+    fn default_codes() -> String {
+        "123456789ABCDEFG".to_string()
+    }
+
+    pub fn count_to_code(&self, count: u8) -> Option<char> {
+        usize::from(count).checked_sub(1).and_then(|index| self.codes.chars().nth(index))
+    }
+
+    pub fn code_to_count(&self, code: char) -> Option<u8> {
+        let code = code.to_ascii_uppercase();
+        self.codes.chars()
+            .position(|c| c == code)
+            .and_then(|pos| u8::try_from(pos + 1).ok())
+    }
+
+    fn validate(&self) -> Result<(), SnError> {
+        if self.min == 0 || self.min > self.max {
+            return Err(SnError::ConfigError("颗粒数量范围无效".to_string()));
+        }
+        let mut seen = HashSet::new();
+        for c in self.codes.chars() {
+            if !(c.is_ascii_digit() || c.is_ascii_uppercase()) {
+                return Err(SnError::ConfigError(format!("颗粒数量代码只能包含数字和大写字母: '{}'", c)));
+            }
+            if !seen.insert(c) {
+                return Err(SnError::ConfigError(format!("颗粒数量代码中有重复字符: '{}'", c)));
+            }
+        }
+        // 范围内每个数量都必须有代码，且能解析回原数量
+        for count in self.min..=self.max {
+            let round_trip = self.count_to_code(count).and_then(|code| self.code_to_count(code));
+            if round_trip != Some(count) {
+                return Err(SnError::ConfigError(format!(
+                    "颗粒数量范围 {}-{} 超出代码字符集 \"{}\" (最多 {} 个)",
+                    self.min, self.max, self.codes, self.codes.chars().count()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 工厂时间设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FactoryConfig {
+    // 工厂时区（UTC 偏移，如 "+08:00"），未设置时使用本机时区
+    #[serde(default)]
+    pub utc_offset: Option<String>,
+    // 生产日开始时刻（0-23 时），早于该时刻的时间计入前一生产日
+    #[serde(default)]
+    pub day_start_hour: u32,
+}
+
+impl FactoryConfig {
+    pub fn offset(&self) -> Result<Option<FixedOffset>, SnError> {
+        self.utc_offset.as_deref()
+            .map(|offset| offset.parse::<FixedOffset>()
+                .map_err(|_| SnError::ConfigError(format!("无效的工厂时区: {} (应为 +08:00 形式)", offset))))
+            .transpose()
+    }
+}
+
+// 已停用的代码：仍可解析已发布的版本号，但不能再用于生成
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetiredCodes {
+    #[serde(default)]
+    pub pcb_sizes: BTreeSet<u8>,
+    #[serde(default)]
+    pub dram_sizes: BTreeSet<char>,
+    #[serde(default)]
+    pub packages: BTreeSet<char>,
 }
 
 // 固件版本配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FirmwareConfig {
     pub base_date: BaseDate,
+    // 纪元切换：日期编码用尽后依次改用的基准日期
+    #[serde(default)]
+    pub rollover_base_dates: Vec<BaseDate>,
+    // 当前纪元（0 为 base_date，n 为 rollover_base_dates 中第 n 个）
+    #[serde(default)]
+    pub era: usize,
     pub base32_chars: String,
-    pub pcb_sizes: HashMap<u8, String>,
-    pub dram_sizes: HashMap<char, i32>,
-    pub packages: HashMap<char, String>,
+    #[serde(default)]
+    pub date_encoding: DateEncoding,
+    pub pcb_sizes: BTreeMap<u8, String>,
+    pub dram_sizes: BTreeMap<char, i32>,
+    pub packages: BTreeMap<char, String>,
     pub chip_count: ChipCount,
+    #[serde(default)]
+    pub retired: RetiredCodes,
     pub format: String,
+    #[serde(default)]
+    pub factory: FactoryConfig,
+}
+
+// 编码能力与各字段的有效取值（均由配置推导）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodecLimits {
+    pub date_encoding: DateEncoding,
+    // 当前纪元
+    pub era: usize,
+    // 当前纪元可编码的天数
+    pub date_capacity: i64,
+    // 可编码的日期范围（闭区间）
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    pub chip_min: u8,
+    pub chip_max: u8,
+    // 各字段的有效代码
+    pub date_chars: Vec<char>,
+    pub pcb_sizes: Vec<u8>,
+    // 与 pcb_sizes 一一对应的代码字符
+    pub pcb_codes: Vec<char>,
+    // 按 DRAM 大小排序
+    pub dram_codes: Vec<char>,
+    pub packages: Vec<char>,
+    pub chip_codes: Vec<char>,
+}
+
+impl CodecLimits {
+    pub fn year_range(&self) -> RangeInclusive<i32> {
+        self.first_date.year()..=self.last_date.year()
+    }
+
+    pub fn chip_range(&self) -> RangeInclusive<u8> {
+        self.chip_min..=self.chip_max
+    }
+
+    pub fn contains_date(&self, date: NaiveDate) -> bool {
+        (self.first_date..=self.last_date).contains(&date)
+    }
+
+    pub fn date_range_desc(&self) -> String {
+        format!("{} 至 {}", self.first_date, self.last_date)
+    }
+}
+
+// 编译时内置的配置文件内容
+pub const BUILTIN_CONFIG: &str = include_str!("../config/fwver.yaml");
+
+// 运行时指定的配置文件（--config）：路径、原文与解析结果
+struct RuntimeConfig {
+    path: PathBuf,
+    text: String,
+    config: FirmwareConfig,
+}
+
+static RUNTIME_CONFIG: OnceLock<RuntimeConfig> = OnceLock::new();
+
+// 读取并验证配置文件
+pub fn load_config_file(path: &Path) -> Result<(String, FirmwareConfig), SnError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| SnError::ConfigError(format!("无法读取配置文件 {}: {}", path.display(), e)))?;
+    let config: FirmwareConfig = serde_yaml::from_str(&text)
+        .map_err(|e| SnError::ConfigError(format!("配置文件 {} 格式错误: {}", path.display(), e)))?;
+    config.validate()?;
+    Ok((text, config))
+}
+
+// 启动时改用指定的配置文件，必须在首次使用 CONFIG 之前调用
+pub fn use_config_file(path: &Path) -> Result<(), SnError> {
+    let (text, config) = load_config_file(path)?;
+    RUNTIME_CONFIG
+        .set(RuntimeConfig { path: path.to_path_buf(), text, config })
+        .map_err(|_| SnError::ConfigError("配置文件只能在启动时指定一次".to_string()))
+}
+
+// 启动时指定的配置文件路径（使用内置配置时为 None）
+pub fn config_path() -> Option<&'static Path> {
+    RUNTIME_CONFIG.get().map(|runtime| runtime.path.as_path())
+}
+
+// 当前生效配置的原文
+pub fn config_text() -> &'static str {
+    RUNTIME_CONFIG.get().map_or(BUILTIN_CONFIG, |runtime| runtime.text.as_str())
 }
 
 impl Default for FirmwareConfig {
     fn default() -> Self {
-        let config_str = include_str!("../config/fwver.yaml");
-        serde_yaml::from_str(config_str)
+        serde_yaml::from_str(BUILTIN_CONFIG)
             .expect("Failed to parse firmware configuration")
     }
 }
 
 impl FirmwareConfig {
     pub fn new() -> Result<Self, SnError> {
-        let config = Self::default();
+        let config = match RUNTIME_CONFIG.get() {
+            Some(runtime) => runtime.config.clone(),
+            None => Self::default(),
+        };
         config.validate()?;
         Ok(config)
     }
@@ -49,22 +268,156 @@ impl FirmwareConfig {
         if self.base32_chars.is_empty() {
             return Err(SnError::ConfigError("Base32字符集不能为空".to_string()));
         }
+        let mut seen = HashSet::new();
+        for c in self.base32_chars.chars() {
+            if !(c.is_ascii_digit() || c.is_ascii_uppercase()) {
+                return Err(SnError::ConfigError(format!("Base32字符集只能包含数字和大写字母: '{}'", c)));
+            }
+            if !seen.insert(c) {
+                return Err(SnError::ConfigError(format!("Base32字符集中有重复字符: '{}'", c)));
+            }
+        }
         if self.pcb_sizes.is_empty() {
             return Err(SnError::ConfigError("PCB尺寸定义不能为空".to_string()));
         }
+        for &size in self.pcb_sizes.keys() {
+            self.pcb_size_code(size)?;
+        }
         if self.dram_sizes.is_empty() {
             return Err(SnError::ConfigError("DRAM大小定义不能为空".to_string()));
         }
+        let mut sizes = HashSet::new();
+        for (&code, &size) in &self.dram_sizes {
+            check_code_char("DRAM大小", code)?;
+            if size != -1 && size <= 0 {
+                return Err(SnError::ConfigError(format!("DRAM大小 {} 无效 (应为正数MB，或 -1 表示 DRAMLess)", size)));
+            }
+            if !sizes.insert(size) {
+                return Err(SnError::ConfigError(format!("DRAM大小 {} 对应多个代码", dram_size_label(size))));
+            }
+        }
         if self.packages.is_empty() {
             return Err(SnError::ConfigError("封装定义不能为空".to_string()));
         }
-        if self.chip_count.min > self.chip_count.max {
-            return Err(SnError::ConfigError("颗粒数量范围无效".to_string()));
+        for &code in self.packages.keys() {
+            check_code_char("封装", code)?;
         }
+        // 停用的代码必须仍在编码表中，才能继续解析旧版本号
+        if let Some(size) = self.retired.pcb_sizes.iter().find(|size| !self.pcb_sizes.contains_key(size)) {
+            return Err(SnError::ConfigError(format!("已停用的PCB尺寸 {} 不在 pcb_sizes 中", size)));
+        }
+        if let Some(code) = self.retired.dram_sizes.iter().find(|code| !self.dram_sizes.contains_key(code)) {
+            return Err(SnError::ConfigError(format!("已停用的DRAM代码 {} 不在 dram_sizes 中", code)));
+        }
+        if let Some(code) = self.retired.packages.iter().find(|code| !self.packages.contains_key(code)) {
+            return Err(SnError::ConfigError(format!("已停用的封装代码 {} 不在 packages 中", code)));
+        }
+        self.chip_count.validate()?;
+        self.factory.offset()?;
+        if self.factory.day_start_hour > 23 {
+            return Err(SnError::ConfigError("生产日开始时刻必须在 0-23 之间".to_string()));
+        }
+        if self.era >= self.era_count() {
+            return Err(SnError::ConfigError(format!("当前纪元 {} 超出范围 (共 {} 个纪元)", self.era, self.era_count())));
+        }
+        let mut previous = None;
+        for era in 0..self.era_count() {
+            let base = self.era_base_date(era)?;
+            if previous.is_some_and(|p| p >= base) {
+                return Err(SnError::ConfigError("纪元基准日期必须依次递增".to_string()));
+            }
+            previous = Some(base);
+        }
+        self.limits()?;
 
         Ok(())
     }
 
+    // 纪元个数
+    pub fn era_count(&self) -> usize {
+        self.rollover_base_dates.len() + 1
+    }
+
+    // 纪元的基准日期
+    pub fn era_base_date(&self, era: usize) -> Result<NaiveDate, SnError> {
+        let base = match era {
+            0 => &self.base_date,
+            era => self.rollover_base_dates.get(era - 1).ok_or_else(|| SnError::InvalidParameter(
+                format!("无效的纪元: {} (可选 0-{})", era, self.era_count() - 1)
+            ))?,
+        };
+        NaiveDate::from_ymd_opt(base.year, base.month, base.day)
+            .ok_or_else(|| SnError::ConfigError(format!("纪元 {} 的基准日期无效", era)))
+    }
+
+    // 纪元可编码的日期范围（闭区间）
+    pub fn era_date_range(&self, era: usize) -> Result<(NaiveDate, NaiveDate), SnError> {
+        let first_date = self.era_base_date(era)?;
+        let tables = CodebookTables::from_config(self, era)?;
+        let codebook = tables.codebook();
+        if !codebook.supports_encoding() {
+            return Err(SnError::ConfigError(format!(
+                "字符集长度 {} 不足以使用 {} 日期编码", self.base32_chars.len(), self.date_encoding.name()
+            )));
+        }
+
+        let last = core::civil_from_days(codebook.last_day());
+        let last_date = NaiveDate::from_ymd_opt(last.year, last.month, last.day)
+            .ok_or_else(|| SnError::ConfigError("可编码日期超出范围".to_string()))?;
+        Ok((first_date, last_date))
+    }
+
+    // 编码能力与各字段取值范围（当前纪元）
+    pub fn limits(&self) -> Result<CodecLimits, SnError> {
+        let (first_date, last_date) = self.era_date_range(self.era)?;
+        let date_chars: Vec<char> = self.base32_chars.chars().map(|c| c.to_ascii_uppercase()).collect();
+        let date_capacity = (last_date - first_date).num_days() + 1;
+
+        let mut pcb_sizes: Vec<u8> = self.pcb_sizes.keys().copied().collect();
+        pcb_sizes.sort_unstable();
+        let pcb_codes = pcb_sizes.iter()
+            .map(|&size| self.pcb_size_code(size))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut dram_sizes: Vec<(char, i32)> = self.dram_sizes.iter()
+            .map(|(&code, &size)| (code.to_ascii_uppercase(), size))
+            .collect();
+        dram_sizes.sort_by_key(|&(code, size)| (size, code));
+
+        let mut packages: Vec<char> = self.packages.keys().map(|c| c.to_ascii_uppercase()).collect();
+        packages.sort_unstable();
+
+        let chip_codes = (self.chip_count.min..=self.chip_count.max)
+            .map(|count| self.chip_count.count_to_code(count))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| SnError::ConfigError("颗粒数量范围无法编码".to_string()))?;
+
+        Ok(CodecLimits {
+            date_encoding: self.date_encoding,
+            era: self.era,
+            date_capacity,
+            first_date,
+            last_date,
+            chip_min: self.chip_count.min,
+            chip_max: self.chip_count.max,
+            date_chars,
+            pcb_sizes,
+            pcb_codes,
+            dram_codes: dram_sizes.into_iter().map(|(code, _)| code).collect(),
+            packages,
+            chip_codes,
+        })
+    }
+
+    // PCB 尺寸在版本号中的代码字符：取日期编码字符集中的第 size 个（0-9 之后为 A-V）
+    pub fn pcb_size_code(&self, size: u8) -> Result<char, SnError> {
+        self.base32_chars.chars()
+            .nth(usize::from(size))
+            .ok_or_else(|| SnError::ConfigError(format!(
+                "PCB尺寸 {} 超出可编码范围 (0-{})", size, self.base32_chars.chars().count().saturating_sub(1)
+            )))
+    }
+
     // 验证方法
     pub fn is_valid_pcb_size(&self, size: u8) -> bool {
         self.pcb_sizes.contains_key(&size)
@@ -82,6 +435,62 @@ impl FirmwareConfig {
         count >= self.chip_count.min && count <= self.chip_count.max
     }
 
+    pub fn is_retired_pcb_size(&self, size: u8) -> bool {
+        self.retired.pcb_sizes.contains(&size)
+    }
+
+    pub fn is_retired_dram_code(&self, code: char) -> bool {
+        self.retired.dram_sizes.contains(&code.to_ascii_uppercase())
+    }
+
+    pub fn is_retired_package(&self, code: char) -> bool {
+        self.retired.packages.contains(&code.to_ascii_uppercase())
+    }
+
+    // 生成新版本号前检查硬件字段未停用
+    pub fn check_not_retired(&self, pcb_size: u8, dram_code: char, package_code: char) -> Result<(), SnError> {
+        let retired = |what: String| Err(SnError::InvalidParameter(format!("{} 已停用，只能用于解析已发布的版本号", what)));
+        if self.is_retired_pcb_size(pcb_size) {
+            return retired(format!("PCB尺寸 {}", pcb_size));
+        }
+        if self.is_retired_dram_code(dram_code) {
+            return retired(format!("DRAM大小 {}", self.get_dram_size_desc(dram_code)));
+        }
+        if self.is_retired_package(package_code) {
+            return retired(format!("封装 {} ({})", package_code.to_ascii_uppercase(), self.get_package_name(package_code)));
+        }
+        Ok(())
+    }
+
+    // 按代码或描述查找封装代码，如 "B" 或 "BGA132"；描述部分匹配到多个封装时报错
+    pub fn find_package(&self, name: &str) -> Result<char, SnError> {
+        let name = name.trim();
+        let mut chars = name.chars();
+        if let (Some(code), None) = (chars.next(), chars.next()) {
+            if self.is_valid_package(code) {
+                return Ok(code.to_ascii_uppercase());
+            }
+        }
+
+        let needle = name.to_ascii_uppercase();
+        if let Some((&code, _)) = self.packages.iter().find(|(_, desc)| desc.to_ascii_uppercase() == needle) {
+            return Ok(code);
+        }
+        let matches: Vec<(&char, &String)> = self.packages.iter()
+            .filter(|(_, desc)| desc.to_ascii_uppercase().contains(&needle))
+            .collect();
+        match matches.as_slice() {
+            [] => Err(SnError::InvalidParameter(format!("未知的封装: {}", name))),
+            [(&code, _)] => Ok(code),
+            _ => {
+                let candidates: Vec<String> = matches.iter().map(|(code, desc)| format!("{} ({})", code, desc)).collect();
+                Err(SnError::InvalidParameter(format!(
+                    "封装 {} 匹配到多个: {}，请使用代码或更完整的名称", name, candidates.join(", ")
+                )))
+            }
+        }
+    }
+
     // 获取描述信息
     pub fn get_pcb_size_name(&self, size: u8) -> String {
         self.pcb_sizes.get(&size)
@@ -89,34 +498,55 @@ impl FirmwareConfig {
             .unwrap_or_else(|| "未知尺寸".to_string())
     }
 
-    #[allow(dead_code)]
     pub fn get_dram_size_desc(&self, code: char) -> String {
         self.dram_sizes.get(&code.to_ascii_uppercase())
-            .map(|&size| {
-                if size == -1 {
-                    "DRAMLess".to_string()
-                } else if size < 1024 {
-                    format!("{}MB", size)
-                } else {
-                    format!("{}GB", size / 1024)
-                }
-            })
+            .map(|&size| dram_size_label(size))
             .unwrap_or_else(|| "未知大小".to_string())
     }
 
+    // 配置中可用于生成的 DRAM 大小(MB)，从小到大（DRAMLess 在最前，不含已停用的）
+    pub fn supported_dram_sizes(&self) -> Vec<i32> {
+        let mut sizes: Vec<i32> = self.dram_sizes.iter()
+            .filter(|(&code, _)| !self.is_retired_dram_code(code))
+            .map(|(_, &size)| size)
+            .collect();
+        sizes.sort_unstable();
+        sizes.dedup();
+        sizes
+    }
+
+    // 与 size_mb 最接近的若干个 DRAM 大小（按从小到大排列）；输入为正数时不把 DRAMLess 当作候选
+    pub fn nearest_dram_sizes(&self, size_mb: i32, count: usize) -> Vec<i32> {
+        let mut sizes = self.supported_dram_sizes();
+        if size_mb > 0 {
+            sizes.retain(|&size| size > 0);
+        }
+        sizes.sort_by_key(|&size| ((i64::from(size) - i64::from(size_mb)).abs(), size));
+        sizes.truncate(count);
+        sizes.sort_unstable();
+        sizes
+    }
+
     pub fn get_package_name(&self, code: char) -> String {
         self.packages.get(&code.to_ascii_uppercase())
             .cloned()
             .unwrap_or_else(|| "未知封装".to_string())
     }
 
-    #[allow(dead_code)]
     pub fn get_config_info(&self) -> String {
+        let date_window = match self.limits() {
+            Ok(limits) => format!(
+                "{} ({} 天，{}编码，纪元 {}/{})",
+                limits.date_range_desc(), limits.date_capacity, self.date_encoding.label(), self.era, self.era_count() - 1
+            ),
+            Err(e) => e.to_string(),
+        };
         format!(
-            "固件版本配置:\n  基准日期: {}-{}-{}\n  PCB尺寸: {} 种\n  DRAM大小: {} 种\n  封装类型: {} 种\n  颗粒数量范围: {} - {}\n  格式: {}",
+            "固件版本配置:\n  基准日期: {}-{}-{}\n  可编码日期: {}\n  PCB尺寸: {} 种\n  DRAM大小: {} 种\n  封装类型: {} 种\n  颗粒数量范围: {} - {}\n  格式: {}",
             self.base_date.year,
             self.base_date.month,
             self.base_date.day,
+            date_window,
             self.pcb_sizes.len(),
             self.dram_sizes.len(),
             self.packages.len(),
@@ -127,17 +557,48 @@ impl FirmwareConfig {
     }
 }
 
+// 版本号中的代码字符只能是数字或大写字母
+fn check_code_char(table: &str, code: char) -> Result<(), SnError> {
+    if code.is_ascii_digit() || code.is_ascii_uppercase() {
+        Ok(())
+    } else {
+        Err(SnError::ConfigError(format!("{}代码只能是数字或大写字母: '{}'", table, code)))
+    }
+}
+
+// DRAM 大小的显示文本，如 DRAMLess、512MB、1GB
+pub fn dram_size_label(size_mb: i32) -> String {
+    if size_mb == -1 {
+        "DRAMLess".to_string()
+    } else if size_mb >= 1024 && size_mb % 1024 == 0 {
+        format!("{}GB", size_mb / 1024)
+    } else {
+        format!("{}MB", size_mb)
+    }
+}
+
 // 主配置结构（为未来扩展预留）
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub firmware: FirmwareConfig,
+    // 各纪元的编码表（启动时建立一次，编解码时直接复用）
+    tables: Vec<CodebookTables>,
 }
 
 impl AppConfig {
     pub fn new() -> Result<Self, SnError> {
-        Ok(Self {
-            firmware: FirmwareConfig::new()?,
-        })
+        let firmware = FirmwareConfig::new()?;
+        let tables = (0..firmware.era_count())
+            .map(|era| CodebookTables::from_config(&firmware, era))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { firmware, tables })
+    }
+
+    // 指定纪元的编码表
+    pub(crate) fn tables(&self, era: usize) -> Result<&CodebookTables, SnError> {
+        self.tables.get(era).ok_or_else(|| SnError::InvalidParameter(
+            format!("无效的纪元: {} (可选 0-{})", era, self.tables.len() - 1)
+        ))
     }
 
     #[allow(dead_code)]
@@ -149,4 +610,64 @@ impl AppConfig {
 lazy_static! {
     pub static ref CONFIG: AppConfig = AppConfig::new()
         .expect("Failed to load configuration");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retired_codes_must_stay_in_tables() {
+        let mut config = FirmwareConfig::default();
+        config.retired.pcb_sizes.insert(3);
+        config.retired.dram_sizes.insert('0');
+        config.retired.packages.insert('A');
+        assert!(config.validate().is_ok());
+
+        config.retired.packages.insert('Z');
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn retired_codes_cannot_be_generated() {
+        let mut config = FirmwareConfig::default();
+        config.retired.packages.insert('A');
+        assert!(config.check_not_retired(1, '3', 'B').is_ok());
+        assert!(config.check_not_retired(1, '3', 'a').is_err());
+
+        config.retired.dram_sizes.insert('3');
+        assert!(config.check_not_retired(1, '3', 'B').is_err());
+        assert!(!config.supported_dram_sizes().contains(&1024));
+    }
+
+    #[test]
+    fn tables_are_built_once_per_era() {
+        let era_count = CONFIG.firmware.era_count();
+        for era in 0..era_count {
+            let base = CONFIG.firmware.era_base_date(era).unwrap();
+            let first_day = core::days_from_civil(base.year(), base.month(), base.day());
+            assert_eq!(CONFIG.tables(era).unwrap().codebook().first_day(), first_day);
+        }
+        assert!(CONFIG.tables(era_count).is_err());
+    }
+
+    #[test]
+    fn nearest_dram_sizes_skip_dramless_for_positive_input() {
+        let config = FirmwareConfig::default();
+        assert_eq!(config.nearest_dram_sizes(100, 3), [128, 256, 512]);
+        assert!(config.nearest_dram_sizes(0, 3).contains(&-1));
+    }
+
+    #[test]
+    fn find_package_reports_ambiguous_names() {
+        let config = FirmwareConfig::default();
+        assert_eq!(config.find_package("b").unwrap(), 'B');
+        assert_eq!(config.find_package("bga168").unwrap(), 'C');
+        assert_eq!(config.find_package("BGA100").unwrap(), 'A');
+        assert_eq!(config.find_package("132").unwrap(), 'B');
+
+        let err = config.find_package("15").unwrap_err();
+        assert!(err.message().contains("B (BGA132/152)") && err.message().contains("H (BGA154)"));
+        assert!(config.find_package("LGA").is_err());
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use crate::config::{self, dram_size_label, FirmwareConfig, CONFIG};
+use crate::error::SnError;
+use crate::firmware_codec::parse_dram_quantity;
+
+// 未通过 --config 指定配置文件时，编辑器默认保存到的文件名
+pub const DEFAULT_CONFIG_FILE: &str = "fwver.yaml";
+
+// 编辑器默认打开的文件：启动时指定的配置文件，否则为 DEFAULT_CONFIG_FILE
+pub fn default_config_path() -> PathBuf {
+    config::config_path().map_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILE), Path::to_path_buf)
+}
+
+// 差异中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+// 编码表编辑器：在内存中修改配置，保存时只改写变动的行（保留注释和格式）
+#[derive(Debug, Clone)]
+pub struct ConfigEditor {
+    pub path: PathBuf,
+    // 加载时的文件内容（文件不存在时为当前生效的配置）
+    pub original: String,
+    pub config: FirmwareConfig,
+    // 文件尚不存在，保存时新建
+    pub is_new: bool,
+    // 加载时的配置，用于找出修改过的条目
+    loaded: FirmwareConfig,
+}
+
+impl ConfigEditor {
+    // 加载配置文件，文件不存在时以当前生效的配置为起点，保存时新建该文件
+    pub fn load(path: &Path) -> Result<Self, SnError> {
+        if path.as_os_str().is_empty() {
+            return Err(SnError::InvalidParameter("请指定配置文件路径".to_string()));
+        }
+        let (original, config, is_new) = if path.exists() {
+            let content = read_file(path)?;
+            let config: FirmwareConfig = serde_yaml::from_str(&content)
+                .map_err(|e| SnError::ConfigError(format!("配置文件格式错误: {}", e)))?;
+            (content, config, false)
+        } else {
+            (config::config_text().to_string(), CONFIG.firmware.clone(), true)
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            original,
+            loaded: config.clone(),
+            config,
+            is_new,
+        })
+    }
+
+    // 保存时写入的内容：在原文件上原位修改变动的条目
+    pub fn render(&self) -> Result<String, SnError> {
+        let content = apply_changes(&self.original, &self.loaded, &self.config);
+        // 原位修改后必须能还原出编辑后的配置，否则（如文件使用了特殊写法）拒绝保存
+        let reloaded: FirmwareConfig = serde_yaml::from_str(&content)
+            .map_err(|e| SnError::ConfigError(format!("修改后的配置无法重新加载: {}", e)))?;
+        if canonical(&reloaded)? != canonical(&self.config)? {
+            return Err(SnError::ConfigError(format!(
+                "无法在保留注释和格式的前提下修改 {}，请手动编辑该文件", self.path.display()
+            )));
+        }
+        Ok(content)
+    }
+
+    pub fn is_modified(&self) -> bool {
+        match (canonical(&self.config), canonical(&self.loaded)) {
+            (Ok(current), Ok(loaded)) => current != loaded,
+            _ => true,
+        }
+    }
+
+    // 与磁盘上当前文件的逐行差异
+    pub fn diff(&self) -> Result<Vec<DiffLine>, SnError> {
+        let on_disk = if self.is_new { self.original.clone() } else { read_file(&self.path)? };
+        Ok(diff_lines(&on_disk, &self.render()?))
+    }
+
+    // 验证后保存：先检查文件未被外部修改并备份原文件，返回备份文件路径（新建文件时为 None）
+    pub fn save(&mut self) -> Result<Option<PathBuf>, SnError> {
+        self.config.validate()?;
+        let content = self.render()?;
+
+        let backup = if self.is_new {
+            // 只新建，不覆盖加载后才出现的同名文件
+            let mut file = std::fs::OpenOptions::new().write(true).create_new(true).open(&self.path)
+                .map_err(|e| SnError::InvalidParameter(format!("无法创建配置文件 {}: {}", self.path.display(), e)))?;
+            file.write_all(content.as_bytes())
+                .map_err(|e| SnError::InvalidParameter(format!("无法写入配置文件 {}: {}", self.path.display(), e)))?;
+            None
+        } else {
+            let on_disk = read_file(&self.path)?;
+            if on_disk != self.original {
+                return Err(SnError::ConfigError(format!(
+                    "配置文件 {} 在加载后已被修改，请重新加载后再编辑", self.path.display()
+                )));
+            }
+            let backup = backup_path(&self.path);
+            std::fs::copy(&self.path, &backup)
+                .map_err(|e| SnError::InvalidParameter(format!("无法创建备份 {}: {}", backup.display(), e)))?;
+            std::fs::write(&self.path, &content)
+                .map_err(|e| SnError::InvalidParameter(format!("无法写入配置文件 {}: {}", self.path.display(), e)))?;
+            Some(backup)
+        };
+        self.original = content;
+        self.loaded = self.config.clone();
+        self.is_new = false;
+        Ok(backup)
+    }
+
+    // 添加或修改 PCB 尺寸
+    pub fn set_pcb_size(&mut self, size: &str, name: &str) -> Result<u8, SnError> {
+        let size: u8 = size.trim().parse()
+            .map_err(|_| SnError::InvalidParameter(format!("无效的PCB尺寸: {}", size)))?;
+        self.config.pcb_size_code(size)?;
+        self.config.pcb_sizes.insert(size, checked_name(name)?);
+        Ok(size)
+    }
+
+    // 添加或修改 DRAM 大小（同一大小只能对应一个代码）
+    pub fn set_dram_size(&mut self, code: &str, size: &str) -> Result<char, SnError> {
+        let code = checked_code(code)?;
+        let size_mb = parse_dram_quantity(size)?;
+        if let Some((&other, _)) = self.config.dram_sizes.iter().find(|&(&c, &s)| c != code && s == size_mb) {
+            return Err(SnError::InvalidParameter(format!(
+                "DRAM大小 {} 已由代码 {} 表示", dram_size_label(size_mb), other
+            )));
+        }
+        self.config.dram_sizes.insert(code, size_mb);
+        Ok(code)
+    }
+
+    // 添加或修改封装
+    pub fn set_package(&mut self, code: &str, name: &str) -> Result<char, SnError> {
+        let code = checked_code(code)?;
+        self.config.packages.insert(code, checked_name(name)?);
+        Ok(code)
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, SnError> {
+    std::fs::read_to_string(path)
+        .map_err(|e| SnError::InvalidParameter(format!("无法读取配置文件 {}: {}", path.display(), e)))
+}
+
+// 规范化文本，用于比较两份配置是否相同
+fn canonical(config: &FirmwareConfig) -> Result<String, SnError> {
+    serde_yaml::to_string(config)
+        .map_err(|e| SnError::ConfigError(format!("配置序列化失败: {}", e)))
+}
+
+// 备份文件名，如 fwver.yaml.bak-20250801-093000
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    path.with_file_name(name)
+}
+
+// 版本号中的代码字符（单个数字或字母，自动转为大写）
+fn checked_code(code: &str) -> Result<char, SnError> {
+    let mut chars = code.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Ok(c.to_ascii_uppercase()),
+        _ => Err(SnError::InvalidParameter(format!("代码应为单个数字或字母: {}", code))),
+    }
+}
+
+fn checked_name(name: &str) -> Result<String, SnError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(SnError::InvalidParameter("名称不能为空".to_string()));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(SnError::InvalidParameter("名称不能包含控制字符".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+// 各表的条目（键, YAML 值），按键排序
+type Entries = Vec<(String, String)>;
+
+fn table_entries<K: ToString, V>(table: &BTreeMap<K, V>, value: impl Fn(&V) -> String) -> Entries {
+    table.iter().map(|(k, v)| (k.to_string(), value(v))).collect()
+}
+
+fn chip_count_entries(config: &FirmwareConfig) -> Entries {
+    vec![
+        ("min".to_string(), config.chip_count.min.to_string()),
+        ("max".to_string(), config.chip_count.max.to_string()),
+        ("codes".to_string(), quote(&config.chip_count.codes)),
+    ]
+}
+
+fn retired_entries(config: &FirmwareConfig) -> Entries {
+    let list = |items: Vec<String>| format!("[{}]", items.join(", "));
+    let retired = &config.retired;
+    vec![
+        ("pcb_sizes".to_string(), list(retired.pcb_sizes.iter().map(u8::to_string).collect())),
+        ("dram_sizes".to_string(), list(retired.dram_sizes.iter().map(|c| quote(&c.to_string())).collect())),
+        ("packages".to_string(), list(retired.packages.iter().map(|c| quote(&c.to_string())).collect())),
+    ]
+}
+
+// 双引号 YAML 字符串
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// 把 old 到 new 的变动原位写入配置文本，未变动的行（含注释）保持不变
+fn apply_changes(text: &str, old: &FirmwareConfig, new: &FirmwareConfig) -> String {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let name = |name: &String| quote(name);
+    let size = |size: &i32| size.to_string();
+    let sections = [
+        ("pcb_sizes", table_entries(&old.pcb_sizes, name), table_entries(&new.pcb_sizes, name)),
+        ("dram_sizes", table_entries(&old.dram_sizes, size), table_entries(&new.dram_sizes, size)),
+        ("packages", table_entries(&old.packages, name), table_entries(&new.packages, name)),
+        ("chip_count", chip_count_entries(old), chip_count_entries(new)),
+        ("retired", retired_entries(old), retired_entries(new)),
+    ];
+    for (section, old, new) in &sections {
+        if old == new {
+            continue;
+        }
+        if section_range(&lines, section).is_none() {
+            lines.push(String::new());
+            lines.push("# 已停用的代码：仍可解析已发布的版本号，但不能再用于生成新版本号".to_string());
+            lines.push(format!("{}:", section));
+        }
+        for (i, (key, value)) in new.iter().enumerate() {
+            if !old.contains(&(key.clone(), value.clone())) {
+                set_entry(&mut lines, section, key, value, &new[i + 1..]);
+            }
+        }
+        for (key, _) in old.iter().filter(|(key, _)| !new.iter().any(|(k, _)| k == key)) {
+            remove_entry(&mut lines, section, key);
+        }
+    }
+
+    let mut content = lines.join("\n");
+    if text.ends_with('\n') || text.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+// 顶层键所在行与其内容块的结束行（不含）
+fn section_range(lines: &[String], section: &str) -> Option<(usize, usize)> {
+    let start = lines.iter().position(|line| top_level_key(line) == Some(section))?;
+    let end = lines[start + 1..].iter()
+        .position(|line| top_level_key(line).is_some())
+        .map_or(lines.len(), |i| start + 1 + i);
+    Some((start, end))
+}
+
+fn top_level_key(line: &str) -> Option<&str> {
+    if line.starts_with(|c: char| c.is_whitespace() || c == '#') {
+        return None;
+    }
+    line.split_once(':').map(|(key, _)| key.trim())
+}
+
+// 缩进行的键（去掉引号）
+fn entry_key(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.len() == line.len() || trimmed.starts_with('#') {
+        return None;
+    }
+    trimmed.split_once(':').map(|(key, _)| key.trim().trim_matches(|c| c == '"' || c == '\''))
+}
+
+fn find_entry(lines: &[String], (start, end): (usize, usize), key: &str) -> Option<usize> {
+    (start + 1..end).find(|&i| entry_key(&lines[i]) == Some(key))
+}
+
+// 修改已有条目的值（保留缩进、键的写法和行尾注释），不存在时按键的顺序插入
+fn set_entry(lines: &mut Vec<String>, section: &str, key: &str, value: &str, later: &[(String, String)]) {
+    let Some(range) = section_range(lines, section) else {
+        return;
+    };
+    if let Some(i) = find_entry(lines, range, key) {
+        let (head, rest) = lines[i].split_once(':').unwrap_or((&lines[i], ""));
+        lines[i] = format!("{}: {}{}", head, value, trailing_comment(rest));
+        return;
+    }
+
+    let (start, end) = range;
+    let entries: Vec<usize> = (start + 1..end).filter(|&i| entry_key(&lines[i]).is_some()).collect();
+    let indent = entries.first()
+        .map(|&i| lines[i].len() - lines[i].trim_start().len())
+        .unwrap_or(2);
+    let at = later.iter()
+        .find_map(|(k, _)| find_entry(lines, range, k))
+        .or_else(|| entries.last().map(|&i| i + 1))
+        .unwrap_or(start + 1);
+    lines.insert(at, format!("{}{}: {}", " ".repeat(indent), key, value));
+}
+
+fn remove_entry(lines: &mut Vec<String>, section: &str, key: &str) {
+    if let Some(i) = section_range(lines, section).and_then(|range| find_entry(lines, range, key)) {
+        lines.remove(i);
+    }
+}
+
+// 值后面的行尾注释（包括前面的空白），引号内的 # 不算
+fn trailing_comment(rest: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in rest.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => {
+                let value_end = rest[..i].trim_end().len();
+                return &rest[value_end..];
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    ""
+}
+
+// 按最长公共子序列求逐行差异
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j]：old[i..] 与 new[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    lines.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BUILTIN_CONFIG;
+
+    fn editor() -> ConfigEditor {
+        let config: FirmwareConfig = serde_yaml::from_str(BUILTIN_CONFIG).unwrap();
+        ConfigEditor {
+            path: PathBuf::from(DEFAULT_CONFIG_FILE),
+            original: BUILTIN_CONFIG.to_string(),
+            loaded: config.clone(),
+            config,
+            is_new: false,
+        }
+    }
+
+    fn changed_lines(editor: &ConfigEditor) -> Vec<DiffLine> {
+        diff_lines(&editor.original, &editor.render().unwrap())
+            .into_iter()
+            .filter(|line| !matches!(line, DiffLine::Same(_)))
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_config_renders_original_text() {
+        let editor = editor();
+        assert!(!editor.is_modified());
+        assert_eq!(editor.render().unwrap(), BUILTIN_CONFIG);
+    }
+
+    #[test]
+    fn edits_change_only_affected_lines() {
+        let mut editor = editor();
+        editor.set_package("H", "BGA154 (新)").unwrap();
+        editor.set_package("e", "BGA200").unwrap();
+        editor.config.chip_count.max = 12;
+        assert!(editor.is_modified());
+
+        assert_eq!(changed_lines(&editor), vec![
+            DiffLine::Added("  E: \"BGA200\"".to_string()),
+            DiffLine::Removed("  H: \"BGA154\"".to_string()),
+            DiffLine::Added("  H: \"BGA154 (新)\"".to_string()),
+            DiffLine::Removed("  max: 16".to_string()),
+            DiffLine::Added("  max: 12".to_string()),
+        ]);
+        // 注释保留
+        let content = editor.render().unwrap();
+        assert!(content.contains("# 纪元切换"));
+        assert!(content.contains("# 颗粒数量代码"));
+    }
+
+    #[test]
+    fn retiring_keeps_entry_and_updates_retired_list() {
+        let mut editor = editor();
+        editor.config.retired.packages.insert('I');
+        editor.config.retired.dram_sizes.insert('0');
+        editor.config.retired.pcb_sizes.insert(3);
+
+        let reloaded: FirmwareConfig = serde_yaml::from_str(&editor.render().unwrap()).unwrap();
+        assert!(reloaded.validate().is_ok());
+        assert!(reloaded.packages.contains_key(&'I'));
+        assert_eq!(reloaded.retired, editor.config.retired);
+        assert_eq!(changed_lines(&editor).len(), 6);
+    }
+
+    #[test]
+    fn retired_section_is_appended_when_missing() {
+        let mut editor = editor();
+        let start = editor.original.find("\nretired:").unwrap();
+        let end = editor.original.find("\nformat:").unwrap();
+        editor.original.replace_range(start..end, "\n");
+        editor.config.retired.packages.insert('A');
+
+        let reloaded: FirmwareConfig = serde_yaml::from_str(&editor.render().unwrap()).unwrap();
+        assert_eq!(reloaded.retired.packages.iter().collect::<Vec<_>>(), vec![&'A']);
+    }
+
+    #[test]
+    fn invalid_entries_are_rejected() {
+        let mut editor = editor();
+        assert!(editor.set_dram_size("8", "1GB").is_err());
+        assert!(editor.set_pcb_size("40", "太大").is_err());
+        assert!(editor.set_package("AB", "BGA").is_err());
+        assert!(editor.set_package("J", " ").is_err());
+
+        editor.config.chip_count.max = 40;
+        assert!(editor.config.validate().is_err());
+    }
+
+    #[test]
+    fn save_backs_up_and_refuses_external_changes() {
+        let dir = std::env::temp_dir().join(format!("ssd_sn_tool_config_editor_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("fwver.yaml");
+        std::fs::write(&path, BUILTIN_CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::load(&path).unwrap();
+        editor.set_package("J", "BGA999").unwrap();
+        let backup = editor.save().unwrap().unwrap();
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), BUILTIN_CONFIG);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("  J: \"BGA999\""));
+        assert!(saved.contains("# 固件版本号配置"));
+        assert!(!editor.is_modified());
+
+        std::fs::write(&path, "changed elsewhere").unwrap();
+        editor.set_package("K", "BGA1000").unwrap();
+        assert!(editor.save().is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_creates_missing_file_from_active_config() {
+        let dir = std::env::temp_dir().join(format!("ssd_sn_tool_config_new_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("fwver.yaml");
+
+        let mut editor = ConfigEditor::load(&path).unwrap();
+        assert!(editor.is_new);
+        assert_eq!(editor.original, config::config_text());
+        editor.set_package("J", "BGA999").unwrap();
+        assert_eq!(editor.save().unwrap(), None);
+        let (_, saved) = config::load_config_file(&path).unwrap();
+        assert_eq!(saved.get_package_name('J'), "BGA999");
+
+        // 加载后才出现的同名文件不会被覆盖
+        let mut other = ConfigEditor::load(&dir.join("other.yaml")).unwrap();
+        std::fs::write(dir.join("other.yaml"), "created elsewhere").unwrap();
+        other.set_package("K", "BGA1000").unwrap();
+        assert!(other.save().is_err());
+        assert!(ConfigEditor::load(Path::new("")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn diff_marks_added_and_removed_lines() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nc\nd"), vec![
+            DiffLine::Same("a".to_string()),
+            DiffLine::Removed("b".to_string()),
+            DiffLine::Same("c".to_string()),
+            DiffLine::Added("d".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,267 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+use chrono::{Datelike, NaiveDate, Weekday};
+use ssd_fw_core::{DATE_CODE_LEN, FIRMWARE_CODE_LEN};
+use crate::error::SnError;
+use crate::firmware_codec::FirmwareCodec;
+
+// 日历导出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalendarFormat {
+    Csv,
+    Html,
+    Ics,
+}
+
+impl CalendarFormat {
+    pub fn from_name(name: &str) -> Result<Self, SnError> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Ok(CalendarFormat::Csv),
+            "html" | "htm" => Ok(CalendarFormat::Html),
+            "ics" | "ical" => Ok(CalendarFormat::Ics),
+            _ => Err(SnError::InvalidParameter(format!("不支持的日历格式: {} (可选 csv|html|ics)", name))),
+        }
+    }
+}
+
+// 日历中的一天
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub date_code: String,
+}
+
+// 两个版本号的日期差
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateDifference {
+    pub first: NaiveDate,
+    pub second: NaiveDate,
+    // second - first 的天数
+    pub days: i64,
+}
+
+impl DateDifference {
+    pub fn ordering(&self) -> Ordering {
+        self.first.cmp(&self.second)
+    }
+}
+
+// 生成日期范围内（闭区间）每天的日期编码
+pub fn calendar(from: NaiveDate, to: NaiveDate) -> Result<Vec<CalendarDay>, SnError> {
+    if from > to {
+        return Err(SnError::InvalidParameter(format!("起始日期 {} 晚于结束日期 {}", from, to)));
+    }
+
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let date_code = FirmwareCodec::date_to_code(date.year(), date.month(), date.day())
+                .map_err(|e| SnError::DateCodeError(format!("{}: {}", date, e.message())))?;
+            Ok(CalendarDay { date, date_code })
+        })
+        .collect()
+}
+
+// 取日期编码（3 位）或完整固件版本号（8 位）对应的日期
+pub fn code_date(code: &str) -> Result<NaiveDate, SnError> {
+    let code = code.trim();
+    let (year, month, day) = match code.len() {
+        DATE_CODE_LEN => FirmwareCodec::code_to_date(code)?,
+        FIRMWARE_CODE_LEN => {
+            let (year, month, day, ..) = FirmwareCodec::parse_firmware_code(code)?;
+            (year, month, day)
+        }
+        _ => return Err(SnError::SnFormatError(format!("应为 3 位日期编码或 8 位固件版本号: {}", code))),
+    };
+    NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| SnError::DateCodeError(format!("无效的日期: {}-{}-{}", year, month, day)))
+}
+
+pub fn date_difference(first: &str, second: &str) -> Result<DateDifference, SnError> {
+    let first = code_date(first)?;
+    let second = code_date(second)?;
+    Ok(DateDifference {
+        first,
+        second,
+        days: (second - first).num_days(),
+    })
+}
+
+// 天数的可读描述，如 "400 天（约 1 年 1 个月）"
+pub fn describe_age(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (today - date).num_days();
+    if days < 0 {
+        return format!("晚于今天 {} 天", -days);
+    }
+
+    let mut months = (today.year() - date.year()) * 12 + today.month() as i32 - date.month() as i32;
+    if today.day() < date.day() {
+        months -= 1;
+    }
+    match (months / 12, months % 12) {
+        (0, 0) => format!("{} 天", days),
+        (0, m) => format!("{} 天（约 {} 个月）", days, m),
+        (y, 0) => format!("{} 天（约 {} 年）", days, y),
+        (y, m) => format!("{} 天（约 {} 年 {} 个月）", days, y, m),
+    }
+}
+
+pub fn render_calendar(days: &[CalendarDay], format: CalendarFormat) -> String {
+    match format {
+        CalendarFormat::Csv => render_csv(days),
+        CalendarFormat::Html => render_html(days),
+        CalendarFormat::Ics => render_ics(days),
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "周一",
+        Weekday::Tue => "周二",
+        Weekday::Wed => "周三",
+        Weekday::Thu => "周四",
+        Weekday::Fri => "周五",
+        Weekday::Sat => "周六",
+        Weekday::Sun => "周日",
+    }
+}
+
+fn render_csv(days: &[CalendarDay]) -> String {
+    let mut out = String::from("date,weekday,date_code\n");
+    for day in days {
+        let _ = writeln!(out, "{},{},{}", day.date, weekday_name(day.date.weekday()), day.date_code);
+    }
+    out
+}
+
+// 每月一张表（周一至周日），适合打印
+fn render_html(days: &[CalendarDay]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>固件日期编码日历</title>\n\
+         <style>\n\
+         body { font-family: sans-serif; }\n\
+         table { border-collapse: collapse; margin-bottom: 24px; page-break-inside: avoid; }\n\
+         th, td { border: 1px solid #999; width: 72px; padding: 4px; text-align: center; }\n\
+         td .day { font-size: 11px; color: #666; }\n\
+         td .code { font-family: monospace; font-size: 16px; font-weight: bold; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+
+    for month in days.chunk_by(|a, b| (a.date.year(), a.date.month()) == (b.date.year(), b.date.month())) {
+        let first = month[0].date;
+        let _ = writeln!(out, "<h2>{}年{}月</h2>", first.year(), first.month());
+        out.push_str("<table>\n<tr>");
+        for weekday in ["周一", "周二", "周三", "周四", "周五", "周六", "周日"] {
+            let _ = write!(out, "<th>{}</th>", weekday);
+        }
+        out.push_str("</tr>\n<tr>");
+
+        let offset = first.weekday().num_days_from_monday() as usize;
+        out.push_str(&"<td></td>".repeat(offset));
+        for (i, day) in month.iter().enumerate() {
+            if i > 0 && (offset + i) % 7 == 0 {
+                out.push_str("</tr>\n<tr>");
+            }
+            let _ = write!(
+                out,
+                "<td><div class=\"day\">{}</div><div class=\"code\">{}</div></td>",
+                day.date.day(), day.date_code
+            );
+        }
+        let filled = (offset + month.len()) % 7;
+        if filled != 0 {
+            out.push_str(&"<td></td>".repeat(7 - filled));
+        }
+        out.push_str("</tr>\n</table>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// 每天一个全天事件（RFC 5545，CRLF 换行）
+fn render_ics(days: &[CalendarDay]) -> String {
+    let mut out = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//ssd_sn_tool//firmware date codes//ZH\r\nCALSCALE:GREGORIAN\r\n");
+    for day in days {
+        let start = day.date.format("%Y%m%d");
+        let end = day.date.succ_opt().unwrap_or(day.date).format("%Y%m%d");
+        let _ = write!(
+            out,
+            "BEGIN:VEVENT\r\nUID:{start}-{code}@ssd_sn_tool\r\nDTSTAMP:{start}T000000Z\r\n\
+             DTSTART;VALUE=DATE:{start}\r\nDTEND;VALUE=DATE:{end}\r\nSUMMARY:日期编码 {code}\r\n\
+             TRANSP:TRANSPARENT\r\nEND:VEVENT\r\n",
+            start = start,
+            end = end,
+            code = day.date_code,
+        );
+    }
+    out.push_str("END:VCALENDAR\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn calendar_covers_closed_range() {
+        let days = calendar(date(2024, 2, 28), date(2024, 3, 1)).unwrap();
+        let dates: Vec<NaiveDate> = days.iter().map(|d| d.date).collect();
+        assert_eq!(dates, [date(2024, 2, 28), date(2024, 2, 29), date(2024, 3, 1)]);
+        for day in &days {
+            assert_eq!(code_date(&day.date_code).unwrap(), day.date);
+        }
+        assert!(calendar(date(2024, 3, 1), date(2024, 2, 28)).is_err());
+        assert!(calendar(date(2000, 1, 1), date(2000, 1, 2)).is_err());
+    }
+
+    #[test]
+    fn code_date_accepts_date_codes_and_full_codes() {
+        let code = FirmwareCodec::generate_firmware_code(2025, 8, 1, 1, 1024, 'A', 4).unwrap();
+        assert_eq!(code_date(&code).unwrap(), date(2025, 8, 1));
+        assert_eq!(code_date(&code[1..4]).unwrap(), date(2025, 8, 1));
+        assert!(code_date("S5ID").is_err());
+    }
+
+    #[test]
+    fn date_difference_is_signed() {
+        let first = FirmwareCodec::date_to_code(2025, 1, 1).unwrap();
+        let second = FirmwareCodec::date_to_code(2025, 3, 1).unwrap();
+        let diff = date_difference(&first, &second).unwrap();
+        assert_eq!(diff.days, 59);
+        assert_eq!(diff.ordering(), Ordering::Less);
+        assert_eq!(date_difference(&second, &first).unwrap().days, -59);
+    }
+
+    #[test]
+    fn describes_age_in_days_months_and_years() {
+        let today = date(2025, 8, 1);
+        assert_eq!(describe_age(date(2025, 7, 20), today), "12 天");
+        assert_eq!(describe_age(date(2025, 5, 1), today), "92 天（约 3 个月）");
+        assert_eq!(describe_age(date(2024, 8, 1), today), "365 天（约 1 年）");
+        assert_eq!(describe_age(date(2024, 6, 2), today), "425 天（约 1 年 1 个月）");
+        assert_eq!(describe_age(date(2025, 8, 3), today), "晚于今天 2 天");
+    }
+
+    #[test]
+    fn renders_each_format() {
+        let days = calendar(date(2025, 9, 30), date(2025, 10, 6)).unwrap();
+        let csv = render_calendar(&days, CalendarFormat::Csv);
+        assert_eq!(csv.lines().count(), 8);
+        assert_eq!(csv.lines().nth(1).unwrap(), format!("2025-09-30,周二,{}", days[0].date_code));
+
+        let html = render_calendar(&days, CalendarFormat::Html);
+        assert!(html.contains("<h2>2025年9月</h2>") && html.contains("<h2>2025年10月</h2>"));
+        // 10 月 1 日为周三，10 月 6 日周一换行
+        assert_eq!(html.matches("<tr>").count(), 2 + 3);
+
+        let ics = render_calendar(&days, CalendarFormat::Ics);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 7);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250930\r\nDTEND;VALUE=DATE:20251001"));
+        assert!(CalendarFormat::from_name("pdf").is_err());
+    }
+}
@@ -9,6 +9,28 @@ pub enum SnError {
     // ParseError(String),
 }
 
+impl SnError {
+    // 错误类别标识（用于 JSON 接口等机器可读场景）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SnError::ConfigError(_) => "config_error",
+            SnError::DateCodeError(_) => "date_code_error",
+            SnError::SnFormatError(_) => "format_error",
+            SnError::InvalidParameter(_) => "invalid_parameter",
+        }
+    }
+
+    // 错误详情（不含类别前缀）
+    pub fn message(&self) -> &str {
+        match self {
+            SnError::ConfigError(msg)
+            | SnError::DateCodeError(msg)
+            | SnError::SnFormatError(msg)
+            | SnError::InvalidParameter(msg) => msg,
+        }
+    }
+}
+
 impl std::error::Error for SnError {}
 
 impl fmt::Display for SnError {
@@ -21,4 +43,4 @@ impl fmt::Display for SnError {
             // SnError::ParseError(msg) => write!(f, "解析错误: {}", msg),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,130 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use crate::config::{FactoryConfig, CONFIG};
+use crate::error::SnError;
+
+// 时钟（测试或可复现构建时可注入固定时间）
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+// 系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// 固定时钟
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+// 某一时刻对应的生产日期：按工厂时区换算，早于生产日开始时刻的计入前一天
+pub fn production_date(now: DateTime<Utc>, factory: &FactoryConfig) -> Result<NaiveDate, SnError> {
+    let local = match factory.offset()? {
+        Some(offset) => now.with_timezone(&offset).naive_local(),
+        None => now.with_timezone(&Local).naive_local(),
+    };
+    Ok((local - Duration::hours(i64::from(factory.day_start_hour))).date())
+}
+
+// 当前生产日期（按配置中的工厂设置）
+pub fn today(clock: &dyn Clock) -> Result<NaiveDate, SnError> {
+    today_in(clock, &CONFIG.firmware.factory)
+}
+
+// 按指定工厂设置计算当前生产日期
+pub fn today_in(clock: &dyn Clock, factory: &FactoryConfig) -> Result<NaiveDate, SnError> {
+    production_date(clock.now(), factory)
+}
+
+// 解析日期参数：YYYY-MM-DD，或 today / now（当前生产日期）
+pub fn resolve_date(value: &str, clock: &dyn Clock) -> Result<NaiveDate, SnError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "today" | "now" | "今天" => today(clock),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+            .map_err(|_| SnError::InvalidParameter(format!("无效的日期 (应为 YYYY-MM-DD 或 today): {}", value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn factory(utc_offset: &str, day_start_hour: u32) -> FactoryConfig {
+        FactoryConfig { utc_offset: Some(utc_offset.to_string()), day_start_hour }
+    }
+
+    #[test]
+    fn production_date_converts_to_factory_offset() {
+        let factory = factory("+08:00", 0);
+        assert_eq!(production_date(at(2025, 8, 1, 15, 59), &factory).unwrap(), date(2025, 8, 1));
+        assert_eq!(production_date(at(2025, 8, 1, 16, 0), &factory).unwrap(), date(2025, 8, 2));
+
+        let factory = self::factory("-05:00", 0);
+        assert_eq!(production_date(at(2025, 8, 1, 4, 59), &factory).unwrap(), date(2025, 7, 31));
+    }
+
+    #[test]
+    fn production_date_rolls_back_before_day_start() {
+        // 工厂时间 2025-08-02 07:59 仍属于 8 月 1 日的夜班
+        let factory = factory("+08:00", 8);
+        assert_eq!(production_date(at(2025, 8, 1, 23, 59), &factory).unwrap(), date(2025, 8, 1));
+        assert_eq!(production_date(at(2025, 8, 2, 0, 0), &factory).unwrap(), date(2025, 8, 2));
+        // 跨年
+        assert_eq!(production_date(at(2025, 12, 31, 20, 0), &factory).unwrap(), date(2025, 12, 31));
+    }
+
+    #[test]
+    fn production_date_rejects_invalid_offset() {
+        assert!(production_date(at(2025, 8, 1, 0, 0), &factory("8h", 0)).is_err());
+    }
+
+    #[test]
+    fn today_uses_injected_clock_and_configured_factory() {
+        // 内置配置：工厂时区 +08:00，生产日从 00:00 开始
+        assert_eq!(today(&FixedClock(at(2025, 8, 1, 15, 59))).unwrap(), date(2025, 8, 1));
+        assert_eq!(today(&FixedClock(at(2025, 8, 1, 16, 0))).unwrap(), date(2025, 8, 2));
+        assert_eq!(today(&FixedClock(at(2025, 12, 31, 16, 0))).unwrap(), date(2026, 1, 1));
+    }
+
+    #[test]
+    fn today_in_applies_day_start_and_offset() {
+        let factory = factory("+08:00", 8);
+        // 工厂时间 2025-08-02 07:30，早于生产日开始时刻，仍为 8 月 1 日
+        assert_eq!(today_in(&FixedClock(at(2025, 8, 1, 23, 30)), &factory).unwrap(), date(2025, 8, 1));
+        // 工厂时间 2025-08-02 08:00
+        assert_eq!(today_in(&FixedClock(at(2025, 8, 2, 0, 0)), &factory).unwrap(), date(2025, 8, 2));
+        // UTC 仍是 8 月 1 日，工厂时区已跨过 8 月 2 日 08:00
+        assert_eq!(today_in(&FixedClock(at(2025, 8, 1, 23, 59)), &self::factory("+09:00", 8)).unwrap(), date(2025, 8, 2));
+        // 西半球：UTC 已是 8 月 2 日，工厂时间仍为 8 月 1 日 20:00
+        assert_eq!(today_in(&FixedClock(at(2025, 8, 2, 1, 0)), &self::factory("-05:00", 0)).unwrap(), date(2025, 8, 1));
+    }
+
+    #[test]
+    fn resolve_date_accepts_today_aliases_and_iso_dates() {
+        // 工厂时间 2025-08-02 00:30
+        let clock = FixedClock(at(2025, 8, 1, 16, 30));
+        for alias in ["today", "NOW", " 今天 "] {
+            assert_eq!(resolve_date(alias, &clock).unwrap(), date(2025, 8, 2));
+        }
+        assert_eq!(resolve_date("2024-02-29", &clock).unwrap(), date(2024, 2, 29));
+        assert!(resolve_date("2025-02-29", &clock).is_err());
+        assert!(resolve_date("yesterday", &clock).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+// C ABI 接口：供量产工具 / 老化测试软件（C/C++）调用
+//
+// 所有函数都不会跨 FFI 边界 panic，出错时返回 SSD_SN_ERR_* 状态码。
+// 头文件 include/ssd_sn_tool.h 由 build.rs 通过 cbindgen 生成。
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use crate::config::CONFIG;
+use crate::error::SnError;
+use crate::firmware_codec::FirmwareCodec;
+
+pub const SSD_SN_OK: i32 = 0;
+pub const SSD_SN_ERR_CONFIG: i32 = 1;
+pub const SSD_SN_ERR_DATE_CODE: i32 = 2;
+pub const SSD_SN_ERR_FORMAT: i32 = 3;
+pub const SSD_SN_ERR_INVALID_PARAMETER: i32 = 4;
+pub const SSD_SN_ERR_NULL_POINTER: i32 = 5;
+pub const SSD_SN_ERR_BUFFER_TOO_SMALL: i32 = 6;
+pub const SSD_SN_ERR_INTERNAL: i32 = 7;
+
+// 固件版本号长度（不含结尾 '\0'）
+pub const SSD_SN_FIRMWARE_CODE_LEN: usize = 8;
+// 日期编码长度（不含结尾 '\0'）
+pub const SSD_SN_DATE_CODE_LEN: usize = 3;
+
+// 固件版本号字段
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SsdFirmwareFields {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub pcb_size: u8,
+    pub dram_size_mb: i32,
+    pub package_code: c_char,
+    pub chip_count: u8,
+}
+
+// 日期
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SsdDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+fn status_of(e: &SnError) -> i32 {
+    match e {
+        SnError::ConfigError(_) => SSD_SN_ERR_CONFIG,
+        SnError::DateCodeError(_) => SSD_SN_ERR_DATE_CODE,
+        SnError::SnFormatError(_) => SSD_SN_ERR_FORMAT,
+        SnError::InvalidParameter(_) => SSD_SN_ERR_INVALID_PARAMETER,
+    }
+}
+
+// 执行闭包并捕获 panic
+fn guard<F: FnOnce() -> i32>(f: F) -> i32 {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(SSD_SN_ERR_INTERNAL)
+}
+
+// 将字符串写入调用方缓冲区（带结尾 '\0'）
+unsafe fn write_c_str(value: &str, out: *mut c_char, out_len: usize) -> i32 {
+    if out.is_null() {
+        return SSD_SN_ERR_NULL_POINTER;
+    }
+    let bytes = value.as_bytes();
+    if out_len < bytes.len() + 1 {
+        return SSD_SN_ERR_BUFFER_TOO_SMALL;
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, out, bytes.len());
+    *out.add(bytes.len()) = 0;
+    SSD_SN_OK
+}
+
+// 读取调用方传入的 C 字符串
+unsafe fn read_c_str<'a>(code: *const c_char) -> Result<&'a str, i32> {
+    if code.is_null() {
+        return Err(SSD_SN_ERR_NULL_POINTER);
+    }
+    CStr::from_ptr(code).to_str().map_err(|_| SSD_SN_ERR_FORMAT)
+}
+
+/// 生成固件版本号，写入 `out`（至少 9 字节）。
+///
+/// # Safety
+/// `fields` 必须指向有效的结构体，`out` 必须指向至少 `out_len` 字节的可写缓冲区。
+#[no_mangle]
+pub unsafe extern "C" fn ssd_sn_generate_firmware_code(
+    fields: *const SsdFirmwareFields,
+    out: *mut c_char,
+    out_len: usize,
+) -> i32 {
+    guard(|| {
+        let Some(f) = fields.as_ref() else {
+            return SSD_SN_ERR_NULL_POINTER;
+        };
+        let package_code = f.package_code as u8 as char;
+        match FirmwareCodec::generate_firmware_code(
+            f.year, f.month, f.day, f.pcb_size, f.dram_size_mb, package_code, f.chip_count
+        ) {
+            Ok(code) => write_c_str(&code, out, out_len),
+            Err(e) => status_of(&e),
+        }
+    })
+}
+
+/// 解析以 '\0' 结尾的固件版本号（当前纪元），结果写入 `out`。
+///
+/// # Safety
+/// `code` 必须是有效的 C 字符串，`out` 必须指向可写的结构体。
+#[no_mangle]
+pub unsafe extern "C" fn ssd_sn_parse_firmware_code(
+    code: *const c_char,
+    out: *mut SsdFirmwareFields,
+) -> i32 {
+    parse_into(code, None, out)
+}
+
+/// 按指定纪元解析固件版本号（纪元切换后同一编码对应不同日期）。
+///
+/// # Safety
+/// `code` 必须是有效的 C 字符串，`out` 必须指向可写的结构体。
+#[no_mangle]
+pub unsafe extern "C" fn ssd_sn_parse_firmware_code_era(
+    code: *const c_char,
+    era: u32,
+    out: *mut SsdFirmwareFields,
+) -> i32 {
+    parse_into(code, Some(era as usize), out)
+}
+
+// 未指定纪元时使用配置中的当前纪元
+unsafe fn parse_into(code: *const c_char, era: Option<usize>, out: *mut SsdFirmwareFields) -> i32 {
+    guard(|| {
+        let code = match read_c_str(code) {
+            Ok(code) => code,
+            Err(status) => return status,
+        };
+        let Some(out) = out.as_mut() else {
+            return SSD_SN_ERR_NULL_POINTER;
+        };
+        let era = era.unwrap_or(CONFIG.firmware.era);
+        match FirmwareCodec::parse_firmware_code_in_era(code, era) {
+            Ok((year, month, day, pcb_size, dram_size_mb, package_code, chip_count)) => {
+                *out = SsdFirmwareFields {
+                    year,
+                    month,
+                    day,
+                    pcb_size,
+                    dram_size_mb,
+                    package_code: package_code.to_ascii_uppercase() as u8 as c_char,
+                    chip_count,
+                };
+                SSD_SN_OK
+            }
+            Err(e) => status_of(&e),
+        }
+    })
+}
+
+/// 将日期编码为 3 位日期编码，写入 `out`（至少 4 字节）。
+///
+/// # Safety
+/// `out` 必须指向至少 `out_len` 字节的可写缓冲区。
+#[no_mangle]
+pub unsafe extern "C" fn ssd_sn_date_to_code(
+    year: i32,
+    month: u32,
+    day: u32,
+    out: *mut c_char,
+    out_len: usize,
+) -> i32 {
+    guard(|| match FirmwareCodec::date_to_code(year, month, day) {
+        Ok(code) => write_c_str(&code, out, out_len),
+        Err(e) => status_of(&e),
+    })
+}
+
+/// 将以 '\0' 结尾的 3 位日期编码解码为日期，结果写入 `out`。
+///
+/// # Safety
+/// `code` 必须是有效的 C 字符串，`out` 必须指向可写的结构体。
+#[no_mangle]
+pub unsafe extern "C" fn ssd_sn_code_to_date(code: *const c_char, out: *mut SsdDate) -> i32 {
+    guard(|| {
+        let code = match read_c_str(code) {
+            Ok(code) => code,
+            Err(status) => return status,
+        };
+        let Some(out) = out.as_mut() else {
+            return SSD_SN_ERR_NULL_POINTER;
+        };
+        match FirmwareCodec::code_to_date(code) {
+            Ok((year, month, day)) => {
+                *out = SsdDate { year, month, day };
+                SSD_SN_OK
+            }
+            Err(e) => status_of(&e),
+        }
+    })
+}
+
+/// 返回状态码对应的名称（静态字符串，调用方无需释放）。
+#[no_mangle]
+pub extern "C" fn ssd_sn_status_name(status: i32) -> *const c_char {
+    let name: &'static [u8] = match status {
+        SSD_SN_OK => b"SSD_SN_OK\0",
+        SSD_SN_ERR_CONFIG => b"SSD_SN_ERR_CONFIG\0",
+        SSD_SN_ERR_DATE_CODE => b"SSD_SN_ERR_DATE_CODE\0",
+        SSD_SN_ERR_FORMAT => b"SSD_SN_ERR_FORMAT\0",
+        SSD_SN_ERR_INVALID_PARAMETER => b"SSD_SN_ERR_INVALID_PARAMETER\0",
+        SSD_SN_ERR_NULL_POINTER => b"SSD_SN_ERR_NULL_POINTER\0",
+        SSD_SN_ERR_BUFFER_TOO_SMALL => b"SSD_SN_ERR_BUFFER_TOO_SMALL\0",
+        SSD_SN_ERR_INTERNAL => b"SSD_SN_ERR_INTERNAL\0",
+        _ => b"SSD_SN_ERR_UNKNOWN\0",
+    };
+    name.as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 仓库中的头文件必须与 src/ffi.rs 一致，修改接口后用
+    // SSD_SN_TOOL_UPDATE_HEADER=1 cargo build --features ffi 重新生成
+    #[test]
+    fn checked_in_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/ssd_sn_tool.h"));
+        let checked_in = include_str!("../include/ssd_sn_tool.h");
+        assert!(generated == checked_in, "include/ssd_sn_tool.h 已过期，请重新生成");
+    }
+
+    #[test]
+    fn status_names_are_nul_terminated() {
+        let name = unsafe { CStr::from_ptr(ssd_sn_status_name(SSD_SN_ERR_FORMAT)) };
+        assert_eq!(name.to_str().unwrap(), "SSD_SN_ERR_FORMAT");
+        let unknown = unsafe { CStr::from_ptr(ssd_sn_status_name(12345)) };
+        assert_eq!(unknown.to_str().unwrap(), "SSD_SN_ERR_UNKNOWN");
+    }
+}
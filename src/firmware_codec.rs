@@ -1,15 +1,19 @@
-use chrono::{DateTime, NaiveDate, TimeZone, Utc, Duration, Datelike};
-use crate::config::CONFIG;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use ssd_fw_core::{self as core, Codebook, Date, FIRMWARE_CODE_LEN};
+use serde::{Deserialize, Deserializer};
+use crate::config::{dram_size_label, FirmwareConfig, CONFIG};
 use crate::error::SnError;
 
+// 固件版本号编解码（基于 no_std 核心 ssd_fw_core 的 std 封装）
 pub struct FirmwareCodec;
 
+// 固件版本号解析结果: (年, 月, 日, PCB尺寸, DRAM大小MB, 封装代码, 颗粒数)
+pub type FirmwareFields = (i32, u32, u32, u8, i32, char, u8);
+
 impl FirmwareCodec {
+    // 当前纪元的基准日期
     pub fn get_base_date() -> Result<DateTime<Utc>, SnError> {
-        let base_date = &CONFIG.firmware.base_date;
-
-        let date = NaiveDate::from_ymd_opt(base_date.year, base_date.month, base_date.day)
-            .ok_or_else(|| SnError::DateCodeError("无效的基准日期".to_string()))?;
+        let date = CONFIG.firmware.era_base_date(CONFIG.firmware.era)?;
 
         let datetime = date.and_hms_opt(12, 0, 0)
             .ok_or_else(|| SnError::DateCodeError("无效的基准日期时间".to_string()))?;
@@ -18,93 +22,83 @@ impl FirmwareCodec {
     }
 
     pub fn date_to_code(year: i32, month: u32, day: u32) -> Result<String, SnError> {
-        let date = NaiveDate::from_ymd_opt(year, month, day)
-            .ok_or_else(|| SnError::DateCodeError("无效的目标日期".to_string()))?;
-
-        let datetime = date.and_hms_opt(12, 0, 0)
-            .ok_or_else(|| SnError::DateCodeError("无效的目标日期时间".to_string()))?;
-
-        let target_date = Utc.from_utc_datetime(&datetime);
-        let base_date = Self::get_base_date()?;
-
-        let duration = target_date - base_date;
-        let days = duration.num_days();
-
-        if days < 0 || days >= 32768 {
-            return Err(SnError::DateCodeError("日期超出范围".to_string()));
-        }
-
-        let base32_chars = CONFIG.firmware.base32_chars.as_bytes();
-        let base = base32_chars.len() as i64;
-        let mut days_val = days;
-        let mut code = String::with_capacity(3);
-
-        for _ in 0..3 {
-            let index = (days_val % base) as usize;
-            code.insert(0, base32_chars[index] as char);
-            days_val /= base;
-        }
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        let code = tables.codebook()
+            .date_to_code(Date { year, month, day })
+            .map_err(|e| map_core_error(e, false))?;
 
-        Ok(code)
+        Ok(code.iter().map(|&c| c as char).collect())
     }
 
     pub fn code_to_date(code: &str) -> Result<(i32, u32, u32), SnError> {
-        if code.len() != 3 {
-            return Err(SnError::DateCodeError("日期编码必须是3位字符".to_string()));
-        }
-
-        let base32_chars = CONFIG.firmware.base32_chars.as_bytes();
-        let base = base32_chars.len();
-        let mut days: i64 = 0;
+        Self::code_to_date_in_era(code, CONFIG.firmware.era)
+    }
 
-        for c in code.chars() {
-            let upper_c = c.to_ascii_uppercase();
-            let pos = base32_chars.iter()
-                .position(|&ch| ch == upper_c as u8)
-                .ok_or_else(|| SnError::DateCodeError(format!("无效日期编码字符: {}", c)))?;
+    // 按指定纪元解析日期编码（纪元切换后同一编码对应不同日期）
+    pub fn code_to_date_in_era(code: &str, era: usize) -> Result<(i32, u32, u32), SnError> {
+        let tables = CONFIG.tables(era)?;
+        let date = tables.codebook()
+            .code_to_date(code.as_bytes())
+            .map_err(|e| map_core_error(e, true))?;
 
-            days = days * base as i64 + pos as i64;
-        }
+        Ok((date.year, date.month, date.day))
+    }
 
-        let base_date = Self::get_base_date()?;
-        let target_date = base_date + Duration::days(days);
+    pub fn dram_size_to_code(size_mb: i32) -> Result<char, SnError> {
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        tables.codebook()
+            .dram_size_to_code(size_mb)
+            .map(|c| c as char)
+            .map_err(|e| map_dram_error(e, size_mb))
+    }
 
-        let naive_date = target_date.naive_utc().date();
-        let year = naive_date.year();
-        let month = naive_date.month();
-        let day = naive_date.day();
+    pub fn dram_code_to_size(c: char) -> Result<i32, SnError> {
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        u8::try_from(c)
+            .map_err(|_| core::Error::InvalidDramCode)
+            .and_then(|c| tables.codebook().dram_code_to_size(c))
+            .map_err(|e| map_core_error(e, true))
+    }
 
-        Ok((year, month, day))
+    // 解析 DRAM 大小输入（如 1GB、1 GiB、512M、1024、DRAMLess），返回配置中支持的大小(MB)
+    pub fn parse_dram_size(input: &str) -> Result<i32, SnError> {
+        let size_mb = parse_dram_quantity(input)?;
+        Self::dram_size_to_code(size_mb)?;
+        Ok(size_mb)
     }
 
-    pub fn dram_size_to_code(size_mb: i32) -> Result<char, SnError> {
-        if size_mb == -1 {
-            return Ok('X');
-        }
+    // PCB 尺寸与版本号中的代码字符互转（0-9 之后为 A-V）
+    pub fn pcb_size_to_char(size: u8) -> Result<char, SnError> {
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        tables.codebook()
+            .pcb_size_to_char(size)
+            .map(|c| c as char)
+            .map_err(|e| map_core_error(e, false))
+    }
 
-        CONFIG.firmware.dram_sizes.iter()
-            .find(|(&code, &size)| code != 'X' && size == size_mb)
-            .map(|(&code, _)| code)
-            .ok_or_else(|| SnError::InvalidParameter("不支持的DRAM大小".to_string()))
+    pub fn char_to_pcb_size(c: char) -> Result<u8, SnError> {
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        u8::try_from(c)
+            .map_err(|_| core::Error::InvalidPcbSize)
+            .and_then(|c| tables.codebook().char_to_pcb_size(c))
+            .map_err(|e| map_core_error(e, true))
     }
 
+    // 颗粒数量与代码字符互转（字符集见配置 chip_count.codes）
     pub fn chip_count_to_char(count: u8) -> Result<char, SnError> {
-        match count {
-            1..=9 => Ok((b'0' + count) as char),
-            10..=15 => Ok((b'A' + (count - 10)) as char),
-            16 => Ok('G'),
-            _ => Err(SnError::InvalidParameter("颗粒个数超出范围 (1-16)".to_string()))
-        }
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        tables.codebook()
+            .chip_count_to_char(count)
+            .map(|c| c as char)
+            .map_err(|e| map_core_error(e, false))
     }
 
     pub fn char_to_chip_count(c: char) -> Result<u8, SnError> {
-        match c {
-            '1'..='9' => Ok(c as u8 - b'0'),
-            'A'..='F' => Ok(10 + (c as u8 - b'A')),
-            'a'..='f' => Ok(10 + (c as u8 - b'a')),
-            'G' | 'g' => Ok(16),
-            _ => Err(SnError::InvalidParameter("无效的颗粒个数代码".to_string()))
-        }
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        u8::try_from(c)
+            .map_err(|_| core::Error::InvalidChipCountCode)
+            .and_then(|c| tables.codebook().char_to_chip_count(c))
+            .map_err(|e| map_core_error(e, true))
     }
 
     pub fn generate_firmware_code(
@@ -116,89 +110,290 @@ impl FirmwareCodec {
         package_code: char,
         chip_count: u8,
     ) -> Result<String, SnError> {
-        // 验证输入参数
-        if !CONFIG.firmware.is_valid_pcb_size(pcb_size) {
-            return Err(SnError::InvalidParameter("无效的PCB尺寸代码".to_string()));
-        }
-
-        if !CONFIG.firmware.is_valid_chip_count(chip_count) {
-            return Err(SnError::InvalidParameter("颗粒个数超出范围 (1-16)".to_string()));
-        }
-
-        if !CONFIG.firmware.is_valid_package(package_code) {
-            return Err(SnError::InvalidParameter("无效的封装代码".to_string()));
-        }
-
-        let date_code = Self::date_to_code(year, month, day)?;
-        let dram_code = Self::dram_size_to_code(dram_size_mb)?;
-        let chip_char = Self::chip_count_to_char(chip_count)?;
+        let package_code = u8::try_from(package_code)
+            .map_err(|_| SnError::InvalidParameter("无效的封装代码".to_string()))?;
 
-        let firmware_code = format!(
-            "S{}{}{}{}{}",
-            date_code,
-            pcb_size,
-            dram_code,
-            package_code.to_ascii_uppercase(),
-            chip_char
-        );
+        let tables = CONFIG.tables(CONFIG.firmware.era)?;
+        let code = tables.codebook()
+            .encode(Date { year, month, day }, pcb_size, dram_size_mb, package_code, chip_count)
+            .map_err(|e| map_dram_error(e, dram_size_mb))?;
+        // 第 6 位为 DRAM 代码
+        CONFIG.firmware.check_not_retired(pcb_size, code[5] as char, package_code as char)?;
 
-        Ok(firmware_code)
+        Ok(code.iter().map(|&c| c as char).collect())
     }
 
-    pub fn parse_firmware_code(firmware_code: &str) -> Result<(i32, u32, u32, u8, i32, char, u8), SnError> {
-        if firmware_code.len() != 8 || !firmware_code.starts_with('S') {
-            return Err(SnError::SnFormatError("无效的固件版本号格式".to_string()));
-        }
-
-        let chars: Vec<char> = firmware_code.chars().collect();
+    pub fn parse_firmware_code(firmware_code: &str) -> Result<FirmwareFields, SnError> {
+        Self::parse_firmware_code_in_era(firmware_code, CONFIG.firmware.era)
+    }
 
-        // 解析日期
-        let date_code: String = chars[1..4].iter().collect();
-        let (year, month, day) = Self::code_to_date(&date_code)?;
+    // 按指定纪元解析固件版本号
+    pub fn parse_firmware_code_in_era(firmware_code: &str, era: usize) -> Result<FirmwareFields, SnError> {
+        let code: &[u8; FIRMWARE_CODE_LEN] = firmware_code.as_bytes()
+            .try_into()
+            .map_err(|_| SnError::SnFormatError("无效的固件版本号格式".to_string()))?;
+
+        let tables = CONFIG.tables(era)?;
+        let fields = tables.codebook()
+            .decode(code)
+            .map_err(|e| map_core_error(e, true))?;
+
+        Ok((
+            fields.date.year,
+            fields.date.month,
+            fields.date.day,
+            fields.pcb_size,
+            fields.dram_size_mb,
+            fields.package_code as char,
+            fields.chip_count,
+        ))
+    }
 
-        // 解析PCB尺寸
-        let pcb_size = chars[4].to_digit(10)
-            .ok_or_else(|| SnError::SnFormatError("无效的PCB尺寸代码".to_string()))? as u8;
+    // 比较两个版本号的硬件字段（PCB/DRAM/封装/颗粒数），返回差异说明
+    pub fn hardware_differences(a: &FirmwareFields, b: &FirmwareFields) -> Vec<String> {
+        let dram_desc = |size_mb: i32| match Self::dram_size_to_code(size_mb) {
+            Ok(code) => CONFIG.firmware.get_dram_size_desc(code),
+            Err(_) => format!("{}MB", size_mb),
+        };
 
-        if !CONFIG.firmware.is_valid_pcb_size(pcb_size) {
-            return Err(SnError::SnFormatError("无效的PCB尺寸代码".to_string()));
+        let mut diffs = Vec::new();
+        if a.3 != b.3 {
+            diffs.push(format!("PCB尺寸不同 ({} ≠ {})", a.3, b.3));
         }
-
-        // 解析DRAM大小
-        let dram_code = chars[5];
-        if !CONFIG.firmware.is_valid_dram_code(dram_code) {
-            return Err(SnError::SnFormatError("无效的DRAM大小代码".to_string()));
+        if a.4 != b.4 {
+            diffs.push(format!("DRAM大小不同 ({} ≠ {})", dram_desc(a.4), dram_desc(b.4)));
         }
-        let dram_size_mb = *CONFIG.firmware.dram_sizes.get(&dram_code.to_ascii_uppercase())
-            .unwrap();
-
-        // 解析颗粒封装
-        let package_code = chars[6];
-        if !CONFIG.firmware.is_valid_package(package_code) {
-            return Err(SnError::SnFormatError("无效的封装代码".to_string()));
+        if !a.5.eq_ignore_ascii_case(&b.5) {
+            diffs.push(format!("封装不同 ({} ≠ {})", a.5, b.5));
         }
-
-        // 解析颗粒个数
-        let chip_count = Self::char_to_chip_count(chars[7])?;
-        if !CONFIG.firmware.is_valid_chip_count(chip_count) {
-            return Err(SnError::SnFormatError("无效的颗粒个数".to_string()));
+        if a.6 != b.6 {
+            diffs.push(format!("颗粒数不同 ({} ≠ {})", a.6, b.6));
         }
-
-        Ok((year, month, day, pcb_size, dram_size_mb, package_code, chip_count))
+        diffs
     }
 
-    #[allow(dead_code)]
     pub fn print_usage() {
         println!("固件版本号生成解析工具");
         println!("固件版本号格式: {}", CONFIG.firmware.format);
-        println!("\n用法:");
-        println!("  生成固件版本号: ssd_tool firmware generate <年> <月> <日> <PCB尺寸> <DRAM大小MB> <封装代码> <颗粒数>");
-        println!("  解析固件版本号: ssd_tool firmware parse <固件版本号>");
+        println!("\n用法（可在最前加 --config <配置文件> 改用运行时配置）:");
+        println!("  生成固件版本号: ssd_tool firmware generate (<年> <月> <日> | <YYYY-MM-DD> | today) <PCB尺寸> <DRAM大小> <封装代码> <颗粒数>");
+        println!("  按预设生成: ssd_tool firmware generate --preset <名称> [<YYYY-MM-DD> | today] [--presets <文件>]");
+        println!("  列出预设: ssd_tool firmware presets [--presets <文件>]");
+        println!("  解析固件版本号: ssd_tool firmware parse <固件版本号> [--era <纪元>]");
+        println!("  写入固件构建: ssd_tool firmware stamp --board <描述文件> [--date YYYY-MM-DD] [--format c|env|rust] [--output <文件>]");
+        println!("  修改镜像版本号: ssd_tool firmware patch-image <镜像> --board <描述文件> [--layout <布局文件>] --output <文件>");
+        println!("  选择兼容镜像: ssd_tool firmware select --repo <目录> (--code <固件版本号> | --identify <identify文件>)");
+        println!("  整理固件文件: ssd_tool firmware organize <源目录> --dest <目标目录> [--template <模板>] [--move] [--dry-run]");
+        println!("  搜索版本号: ssd_tool firmware search <模式> [--from <日期>] [--to <日期>] [--count] [--filter <文件>]");
+        println!("  导出日期编码日历: ssd_tool firmware calendar [--year <年> | --from <日期> --to <日期>] [--format csv|html|ics]");
+        println!("  日期差: ssd_tool firmware date-diff <版本号1> <版本号2>");
+        println!("  比较版本号: ssd_tool firmware compare <版本号1> <版本号2> [<版本号3> ...]");
+        println!("  固件时长: ssd_tool firmware age <版本号> [--today <日期>]");
         println!("  查看配置: ssd_tool firmware config");
+        println!("  发布目录: ssd_tool catalog [--catalog <文件>] add|status|query ...");
+        println!("  HTTP服务: ssd_tool serve --bind 127.0.0.1:8080 [--allow-remote]");
+        println!("  生成编码表: ssd_tool codegen --lang c|rust|python|json [--output <文件>]");
         println!("\n示例:");
-        println!("  生成(有DRAM): ssd_tool firmware generate 2025 12 1 1 1024 A 4");
-        println!("  生成(DRAMLess): ssd_tool firmware generate 2025 12 1 1 -1 A 4");
+        println!("  生成(有DRAM): ssd_tool firmware generate 2025 12 1 1 1GB A 4");
+        println!("  生成(DRAMLess): ssd_tool firmware generate 2025 12 1 1 DRAMLess A 4");
         println!("  生成(16颗粒): ssd_tool firmware generate 2025 12 1 1 1024 A 16");
+        println!("  生成(当前生产日期): ssd_tool firmware generate today 1 1024 A 4");
         println!("  解析: ssd_tool firmware parse S01E1A4");
     }
-}
\ No newline at end of file
+}
+
+// 由运行时配置构建的编码表（单个纪元）
+#[derive(Debug, Clone)]
+pub(crate) struct CodebookTables {
+    base_days: i64,
+    alphabet: Vec<u8>,
+    date_encoding: core::DateEncoding,
+    pcb_sizes: Vec<u8>,
+    dram_sizes: Vec<(u8, i32)>,
+    packages: Vec<u8>,
+    chip_min: u8,
+    chip_max: u8,
+    chip_codes: Vec<u8>,
+}
+
+impl CodebookTables {
+    pub(crate) fn from_config(config: &FirmwareConfig, era: usize) -> Result<Self, SnError> {
+        let base_date = config.era_base_date(era)?;
+
+        Ok(Self {
+            base_days: core::days_from_civil(base_date.year(), base_date.month(), base_date.day()),
+            alphabet: config.base32_chars.bytes().collect(),
+            date_encoding: config.date_encoding.to_core(),
+            pcb_sizes: config.pcb_sizes.keys().copied().collect(),
+            dram_sizes: config.dram_sizes.iter()
+                .map(|(&code, &size)| (code.to_ascii_uppercase() as u8, size))
+                .collect(),
+            packages: config.packages.keys()
+                .map(|&code| code.to_ascii_uppercase() as u8)
+                .collect(),
+            chip_min: config.chip_count.min,
+            chip_max: config.chip_count.max,
+            chip_codes: config.chip_count.codes.to_ascii_uppercase().into_bytes(),
+        })
+    }
+
+    pub(crate) fn codebook(&self) -> Codebook<'_> {
+        Codebook {
+            base_days: self.base_days,
+            alphabet: &self.alphabet,
+            date_encoding: self.date_encoding,
+            pcb_sizes: &self.pcb_sizes,
+            dram_sizes: &self.dram_sizes,
+            packages: &self.packages,
+            chip_min: self.chip_min,
+            chip_max: self.chip_max,
+            chip_codes: &self.chip_codes,
+        }
+    }
+}
+
+// 供 serde 使用：DRAM 大小可写为数值(MB)或带单位的文本
+pub fn deserialize_dram_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DramSize {
+        Mb(i64),
+        Text(String),
+    }
+
+    let text = match DramSize::deserialize(deserializer)? {
+        DramSize::Mb(size) => size.to_string(),
+        DramSize::Text(text) => text,
+    };
+    FirmwareCodec::parse_dram_size(&text).map_err(|e| serde::de::Error::custom(e.message()))
+}
+
+// DRAM 大小文本转换为 MB（不检查是否为配置中的大小）
+pub(crate) fn parse_dram_quantity(input: &str) -> Result<i32, SnError> {
+    let invalid = || SnError::InvalidParameter(format!(
+        "无效的DRAM大小: {} (应为 1GB、512MB、1024 或 DRAMLess)", input
+    ));
+
+    let text: String = input.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_lowercase();
+    if matches!(text.as_str(), "dramless" | "none" | "-1") {
+        return Ok(-1);
+    }
+
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let scale = match unit {
+        "" | "m" | "mb" | "mib" => 1.0,
+        "g" | "gb" | "gib" => 1024.0,
+        "t" | "tb" | "tib" => 1024.0 * 1024.0,
+        _ => return Err(invalid()),
+    };
+    let size = number.parse::<f64>().map_err(|_| invalid())? * scale;
+    if size < 1.0 || size.fract() != 0.0 || size > f64::from(i32::MAX) {
+        return Err(invalid());
+    }
+    Ok(size as i32)
+}
+
+// DRAM 大小不受支持时列出最接近的可选值
+fn map_dram_error(e: core::Error, size_mb: i32) -> SnError {
+    match e {
+        core::Error::InvalidDramSize => {
+            let nearest: Vec<String> = CONFIG.firmware.nearest_dram_sizes(size_mb, 3)
+                .into_iter()
+                .map(dram_size_label)
+                .collect();
+            SnError::InvalidParameter(format!(
+                "不支持的DRAM大小: {} (最接近的可选值: {})", dram_size_label(size_mb), nearest.join(", ")
+            ))
+        }
+        e => map_core_error(e, false),
+    }
+}
+
+// 核心错误转换为 SnError（parsing 为 true 时表示解析场景）
+fn map_core_error(e: core::Error, parsing: bool) -> SnError {
+    let field_error = |msg: &str| if parsing {
+        SnError::SnFormatError(msg.to_string())
+    } else {
+        SnError::InvalidParameter(msg.to_string())
+    };
+
+    match e {
+        core::Error::InvalidDate => SnError::DateCodeError("无效的目标日期".to_string()),
+        core::Error::DateOutOfRange => match CONFIG.firmware.limits() {
+            Ok(limits) => SnError::DateCodeError(format!("日期超出范围 ({})", limits.date_range_desc())),
+            Err(_) => SnError::DateCodeError("日期超出范围".to_string()),
+        },
+        core::Error::InvalidDateCodeLength => SnError::DateCodeError("日期编码必须是3位字符".to_string()),
+        core::Error::InvalidDateChar(c) => SnError::DateCodeError(format!("无效日期编码字符: {}", c as char)),
+        core::Error::InvalidDateCode => SnError::DateCodeError(format!(
+            "日期编码超出{}编码的取值范围", CONFIG.firmware.date_encoding.label()
+        )),
+        core::Error::InvalidFormat => SnError::SnFormatError("无效的固件版本号格式".to_string()),
+        core::Error::InvalidPcbSize => field_error("无效的PCB尺寸代码"),
+        core::Error::InvalidDramSize => SnError::InvalidParameter("不支持的DRAM大小".to_string()),
+        core::Error::InvalidDramCode => SnError::SnFormatError("无效的DRAM大小代码".to_string()),
+        core::Error::InvalidPackage => field_error("无效的封装代码"),
+        core::Error::InvalidChipCount if parsing => SnError::SnFormatError("无效的颗粒个数".to_string()),
+        core::Error::InvalidChipCount => SnError::InvalidParameter(format!(
+            "颗粒个数超出范围 ({}-{})", CONFIG.firmware.chip_count.min, CONFIG.firmware.chip_count.max
+        )),
+        core::Error::InvalidChipCountCode => SnError::InvalidParameter("无效的颗粒个数代码".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BaseDate, DateEncoding};
+
+    fn config_with(encoding: DateEncoding) -> FirmwareConfig {
+        let mut config = CONFIG.firmware.clone();
+        config.date_encoding = encoding;
+        config.rollover_base_dates = vec![BaseDate { year: 2031, month: 7, day: 9 }];
+        config
+    }
+
+    #[test]
+    fn date_codes_round_trip_for_each_encoding_and_era() {
+        for encoding in [DateEncoding::DayCount, DateEncoding::IsoWeek, DateEncoding::YearDay] {
+            let config = config_with(encoding);
+            for era in 0..config.era_count() {
+                let tables = CodebookTables::from_config(&config, era).unwrap();
+                let codebook = tables.codebook();
+                let (first, last) = (codebook.first_day(), codebook.last_day());
+
+                let mut days: Vec<i64> = (first..=last).step_by(13).collect();
+                days.push(last);
+                for day in days {
+                    let date = core::civil_from_days(day);
+                    let code = codebook.date_to_code(date).unwrap();
+                    let decoded = codebook.code_to_date(&code).unwrap();
+                    // ISO 周编码只精确到周，解码为该周周一（基准日期所在的周为基准日期）
+                    let expected = match encoding {
+                        DateEncoding::IsoWeek => {
+                            let (year, week) = core::iso_week_from_days(day);
+                            core::civil_from_days(core::iso_week_start(year, week).max(first))
+                        }
+                        _ => date,
+                    };
+                    assert_eq!(decoded, expected, "{:?} 纪元 {} 日期 {:?}", encoding, era, date);
+                }
+
+                assert!(codebook.date_to_code(core::civil_from_days(first - 1)).is_err());
+                assert!(codebook.date_to_code(core::civil_from_days(last + 1)).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn same_code_decodes_per_era() {
+        let config = config_with(DateEncoding::DayCount);
+        let era0 = CodebookTables::from_config(&config, 0).unwrap();
+        let era1 = CodebookTables::from_config(&config, 1).unwrap();
+        let code = era1.codebook().date_to_code(Date { year: 2031, month: 7, day: 9 }).unwrap();
+        assert_eq!(&code, b"000");
+        assert_eq!(era0.codebook().code_to_date(&code).unwrap(), Date { year: 2010, month: 4, day: 15 });
+        assert!(CodebookTables::from_config(&config, 2).is_err());
+    }
+}
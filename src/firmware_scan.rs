@@ -0,0 +1,49 @@
+use crate::firmware_codec::FirmwareCodec;
+
+// 在数据中找到的固件版本号
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeMatch {
+    pub offset: usize,
+    pub code: String,
+}
+
+// 扫描字节数据（固件镜像、文本等）中所有可解析的固件版本号
+pub fn scan_codes(data: &[u8]) -> Vec<CodeMatch> {
+    scan_candidates(data)
+        .into_iter()
+        .filter(|m| FirmwareCodec::parse_firmware_code(&m.code).is_ok())
+        .collect()
+}
+
+// 扫描候选版本号：S 开头的 8 位字母数字串（不检查能否解析）
+//
+// 候选位置要求前后不是字母或数字，避免从更长的 ASCII 串中截取误报。
+pub fn scan_candidates(data: &[u8]) -> Vec<CodeMatch> {
+    const LEN: usize = 8;
+    let mut matches = Vec::new();
+    if data.len() < LEN {
+        return matches;
+    }
+
+    for offset in 0..=data.len() - LEN {
+        if data[offset] != b'S' {
+            continue;
+        }
+        if offset > 0 && data[offset - 1].is_ascii_alphanumeric() {
+            continue;
+        }
+        if data.get(offset + LEN).is_some_and(|b| b.is_ascii_alphanumeric()) {
+            continue;
+        }
+
+        let window = &data[offset..offset + LEN];
+        if !window.iter().all(|b| b.is_ascii_alphanumeric()) {
+            continue;
+        }
+
+        let code: String = window.iter().map(|&b| b as char).collect();
+        matches.push(CodeMatch { offset, code });
+    }
+
+    matches
+}
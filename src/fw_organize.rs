@@ -0,0 +1,251 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use sha2::{Digest, Sha256};
+use crate::config::CONFIG;
+use crate::error::SnError;
+use crate::firmware_codec::{FirmwareCodec, FirmwareFields};
+use crate::firmware_scan;
+use crate::fw_repo;
+
+// 默认目标路径模板
+pub const DEFAULT_TEMPLATE: &str = "{year}/{month}/{pcb_desc}/{package_desc}_{chips}ch/{code}.{ext}";
+
+// 清单文件名（sha256sum 格式）
+pub const MANIFEST_FILE: &str = "MANIFEST.sha256";
+
+// 版本号来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CodeSource {
+    FileName,
+    Content,
+}
+
+impl CodeSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CodeSource::FileName => "文件名",
+            CodeSource::Content => "镜像内容",
+        }
+    }
+}
+
+// 整理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrganizeMode {
+    Copy,
+    Move,
+}
+
+// 待整理的文件
+#[derive(Debug, Clone)]
+pub struct OrganizeEntry {
+    pub source: PathBuf,
+    // 相对于目标目录的路径
+    pub target: PathBuf,
+    pub code: String,
+    pub found_in: CodeSource,
+    pub sha256: String,
+}
+
+// 跳过的文件及原因
+#[derive(Debug, Clone)]
+pub struct Skipped {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// 从文件名或镜像内容中查找固件版本号
+pub fn find_code(path: &Path, data: &[u8]) -> Result<(String, CodeSource), String> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if let Some(m) = firmware_scan::scan_codes(name.as_bytes()).into_iter().next() {
+        return Ok((m.code, CodeSource::FileName));
+    }
+
+    let codes: BTreeSet<String> = firmware_scan::scan_codes(data)
+        .into_iter()
+        .map(|m| m.code)
+        .collect();
+    match codes.len() {
+        1 => Ok((codes.into_iter().next().unwrap_or_default(), CodeSource::Content)),
+        0 => Err("文件名与镜像内容中均未找到固件版本号".to_string()),
+        _ => Err(format!(
+            "镜像内容中包含多个不同的固件版本号: {}",
+            codes.into_iter().collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+// 按模板生成相对路径（结果必须位于目标目录之内）
+pub fn render_template(template: &str, code: &str, fields: &FirmwareFields, source: &Path) -> Result<PathBuf, SnError> {
+    let (year, month, day, pcb_size, dram_size_mb, package_code, chip_count) = *fields;
+    let dram_code = FirmwareCodec::dram_size_to_code(dram_size_mb).unwrap_or('?');
+    let ext = source.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "bin".to_string());
+    let name = source.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let values = [
+        ("{code}", code.to_string()),
+        ("{date_code}", code[1..4].to_string()),
+        ("{year}", year.to_string()),
+        ("{month}", format!("{:02}", month)),
+        ("{day}", format!("{:02}", day)),
+        ("{pcb}", pcb_size.to_string()),
+        ("{pcb_desc}", CONFIG.firmware.get_pcb_size_name(pcb_size)),
+        ("{dram}", dram_code.to_string()),
+        ("{dram_desc}", CONFIG.firmware.get_dram_size_desc(dram_code)),
+        ("{package}", package_code.to_string()),
+        ("{package_desc}", CONFIG.firmware.get_package_name(package_code)),
+        ("{chips}", chip_count.to_string()),
+        ("{ext}", ext),
+        ("{name}", name),
+    ];
+
+    // 逐个路径段替换，描述中的 '/' 等字符不会产生额外目录
+    let target: PathBuf = template
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let mut segment = segment.to_string();
+            for (key, value) in &values {
+                segment = segment.replace(key, &sanitize(value));
+            }
+            segment
+        })
+        .collect();
+
+    // 替换后不能出现 '..'、'.' 或绝对路径，否则文件会被写到目标目录以外
+    if target.as_os_str().is_empty() || !target.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(SnError::InvalidParameter(format!(
+            "模板生成的路径 {} 无效：不能为空、包含 '.' / '..' 或绝对路径", target.display()
+        )));
+    }
+    Ok(target)
+}
+
+// 生成整理计划（不修改文件）
+pub fn plan(source_dir: &Path, template: &str) -> Result<(Vec<OrganizeEntry>, Vec<Skipped>), SnError> {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+
+    for path in fw_repo::collect_images(source_dir)? {
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                skipped.push(Skipped { path, reason: format!("无法读取: {}", e) });
+                continue;
+            }
+        };
+
+        let (code, found_in) = match find_code(&path, &data) {
+            Ok(found) => found,
+            Err(reason) => {
+                skipped.push(Skipped { path, reason });
+                continue;
+            }
+        };
+
+        let fields = FirmwareCodec::parse_firmware_code(&code)?;
+        let target = match render_template(template, &code, &fields, &path) {
+            Ok(target) => target,
+            Err(e) => {
+                skipped.push(Skipped { path, reason: e.message().to_string() });
+                continue;
+            }
+        };
+        entries.push(OrganizeEntry {
+            target,
+            source: path,
+            code,
+            found_in,
+            sha256: sha256_hex(&data),
+        });
+    }
+
+    Ok((entries, skipped))
+}
+
+// 执行整理，返回因目标冲突等原因未处理的文件
+pub fn execute(entries: &[OrganizeEntry], dest_root: &Path, mode: OrganizeMode) -> Result<Vec<Skipped>, SnError> {
+    let mut skipped = Vec::new();
+
+    for entry in entries {
+        let dest = dest_root.join(&entry.target);
+        if dest.exists() {
+            let reason = match std::fs::read(&dest) {
+                Ok(existing) if sha256_hex(&existing) == entry.sha256 => "目标文件已存在（内容相同）".to_string(),
+                _ => format!("目标文件已存在且内容不同: {}", dest.display()),
+            };
+            skipped.push(Skipped { path: entry.source.clone(), reason });
+            continue;
+        }
+
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| SnError::InvalidParameter(format!("无法创建目录 {}: {}", parent.display(), e)))?;
+        }
+
+        let result = match mode {
+            OrganizeMode::Copy => std::fs::copy(&entry.source, &dest).map(|_| ()),
+            // 跨分区时 rename 会失败，退回为复制后删除
+            OrganizeMode::Move => std::fs::rename(&entry.source, &dest).or_else(|_| {
+                std::fs::copy(&entry.source, &dest)?;
+                std::fs::remove_file(&entry.source)
+            }),
+        };
+        if let Err(e) = result {
+            skipped.push(Skipped { path: entry.source.clone(), reason: format!("写入失败: {}", e) });
+        }
+    }
+
+    Ok(skipped)
+}
+
+// 生成 sha256sum 格式的清单内容
+pub fn manifest(entries: &[OrganizeEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{}  {}\n", e.sha256, e.target.to_string_lossy().replace('\\', "/")))
+        .collect()
+}
+
+// 替换路径中不允许出现的字符
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, source: &str) -> Result<PathBuf, SnError> {
+        let code = "S5ID13A4";
+        let fields = FirmwareCodec::parse_firmware_code(code).unwrap();
+        render_template(template, code, &fields, Path::new(source))
+    }
+
+    #[test]
+    fn renders_relative_path() {
+        let target = render("{date_code}/{code}.{ext}", "fw/S5ID13A4.bin").unwrap();
+        assert_eq!(target, PathBuf::from("5ID").join("S5ID13A4.bin"));
+    }
+
+    #[test]
+    fn rejects_paths_outside_destination() {
+        assert!(render("../{code}.{ext}", "a.bin").is_err());
+        assert!(render("{pcb}/../../{code}", "a.bin").is_err());
+        assert!(render("./{code}", "a.bin").is_err());
+        // 文件名渲染出 '..' 也要拒绝
+        assert!(render("{name}", "...bin").is_err());
+        #[cfg(windows)]
+        assert!(render("C:\\{code}", "a.bin").is_err());
+    }
+}
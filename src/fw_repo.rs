@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::error::SnError;
+use crate::firmware_codec::{FirmwareCodec, FirmwareFields};
+use crate::firmware_scan;
+
+// 视为固件镜像的扩展名
+const IMAGE_EXTENSIONS: &[&str] = &["bin", "img", "fw", "rom", "hex"];
+
+// 标记文件：相对路径 -> 固件版本号，用于文件名中不含版本号的镜像
+pub const TAG_FILE: &str = "fwindex.yaml";
+
+// 固件仓库中的镜像
+#[derive(Debug, Clone)]
+pub struct RepoImage {
+    pub path: PathBuf,
+    pub code: String,
+    pub fields: FirmwareFields,
+}
+
+// 被排除的镜像及原因
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub path: PathBuf,
+    pub code: Option<String>,
+    pub reasons: Vec<String>,
+}
+
+// 选择结果
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub best: Option<RepoImage>,
+    // 兼容镜像，按日期从新到旧
+    pub compatible: Vec<RepoImage>,
+    pub rejected: Vec<Rejection>,
+}
+
+// 建立仓库索引：版本号取自标记文件或文件名
+pub fn index_repository(root: &Path) -> Result<(Vec<RepoImage>, Vec<Rejection>), SnError> {
+    let tags = load_tags(root)?;
+    let files = collect_images(root)?;
+
+    let mut images = Vec::new();
+    let mut rejected = Vec::new();
+    for path in files {
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        let code = tags.get(&relative).cloned().or_else(|| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            firmware_scan::scan_codes(name.as_bytes()).into_iter().next().map(|m| m.code)
+        });
+
+        match code {
+            Some(code) => match FirmwareCodec::parse_firmware_code(&code) {
+                Ok(fields) => images.push(RepoImage { path, code, fields }),
+                Err(e) => rejected.push(Rejection {
+                    path,
+                    code: Some(code),
+                    reasons: vec![format!("版本号无法解析: {}", e)],
+                }),
+            },
+            None => rejected.push(Rejection {
+                path,
+                code: None,
+                reasons: vec!["文件名与标记文件中均未找到固件版本号".to_string()],
+            }),
+        }
+    }
+
+    Ok((images, rejected))
+}
+
+// 按硬件字段筛选兼容镜像，并选出日期最新的一个
+pub fn select(target: &FirmwareFields, images: Vec<RepoImage>) -> Selection {
+    let mut compatible = Vec::new();
+    let mut rejected = Vec::new();
+
+    for image in images {
+        let reasons = FirmwareCodec::hardware_differences(target, &image.fields);
+        if reasons.is_empty() {
+            compatible.push(image);
+        } else {
+            rejected.push(Rejection {
+                path: image.path,
+                code: Some(image.code),
+                reasons,
+            });
+        }
+    }
+
+    compatible.sort_by(|a, b| {
+        let date_a = (a.fields.0, a.fields.1, a.fields.2);
+        let date_b = (b.fields.0, b.fields.1, b.fields.2);
+        date_b.cmp(&date_a).then_with(|| a.path.cmp(&b.path))
+    });
+
+    Selection {
+        best: compatible.first().cloned(),
+        compatible,
+        rejected,
+    }
+}
+
+// 从 identify 数据中读取固件版本号
+//
+// 512 字节按 ATA IDENTIFY DEVICE 处理（字 23-26，每字高低字节交换），
+// 4096 字节按 NVMe Identify Controller 处理（字节 64-71）。
+pub fn firmware_code_from_identify(data: &[u8]) -> Result<String, SnError> {
+    let raw: Vec<u8> = match data.len() {
+        512 => data[46..54].chunks(2).flat_map(|w| [w[1], w[0]]).collect(),
+        4096 => data[64..72].to_vec(),
+        len => {
+            return Err(SnError::InvalidParameter(format!(
+                "无法识别的 identify 数据长度: {} (应为 512 或 4096 字节)", len
+            )))
+        }
+    };
+
+    let code = String::from_utf8_lossy(&raw).trim().to_string();
+    FirmwareCodec::parse_firmware_code(&code)
+        .map_err(|e| SnError::SnFormatError(format!("identify 中的固件版本 \"{}\" 无法解析: {}", code, e)))?;
+    Ok(code)
+}
+
+fn load_tags(root: &Path) -> Result<HashMap<String, String>, SnError> {
+    let path = root.join(TAG_FILE);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| SnError::InvalidParameter(format!("无法读取 {}: {}", path.display(), e)))?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| SnError::ConfigError(format!("{} 格式错误: {}", TAG_FILE, e)))
+}
+
+// 递归收集目录中的镜像文件（按路径排序）
+pub fn collect_images(root: &Path) -> Result<Vec<PathBuf>, SnError> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), SnError> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| SnError::InvalidParameter(format!("无法读取目录 {}: {}", dir.display(), e)))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // file_type 不跟随符号链接：不进入链接的目录，避免链接成环时无限递归
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_files(&path, files)?;
+        } else if (file_type.is_file() || (file_type.is_symlink() && path.is_file())) && is_image(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| {
+            let ext = ext.to_string_lossy().to_ascii_lowercase();
+            IMAGE_EXTENSIONS.contains(&ext.as_str())
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(name: &str, code: &str) -> RepoImage {
+        RepoImage {
+            path: PathBuf::from(name),
+            code: code.to_string(),
+            fields: FirmwareCodec::parse_firmware_code(code).unwrap(),
+        }
+    }
+
+    fn code(year: i32, month: u32, day: u32, dram_size_mb: i32, chips: u8) -> String {
+        FirmwareCodec::generate_firmware_code(year, month, day, 1, dram_size_mb, 'A', chips).unwrap()
+    }
+
+    #[test]
+    fn select_prefers_newest_compatible_image() {
+        let target = FirmwareCodec::parse_firmware_code(&code(2025, 6, 1, 1024, 4)).unwrap();
+        let images = vec![
+            image("b_old.bin", &code(2024, 1, 1, 1024, 4)),
+            image("wrong_dram.bin", &code(2025, 7, 1, 2048, 4)),
+            image("new.bin", &code(2025, 3, 1, 1024, 4)),
+            image("a_old.bin", &code(2024, 1, 1, 1024, 4)),
+            image("wrong_chips.bin", &code(2025, 8, 1, 1024, 8)),
+        ];
+
+        let selection = select(&target, images);
+        let order: Vec<_> = selection.compatible.iter().map(|i| i.path.to_string_lossy().to_string()).collect();
+        assert_eq!(order, ["new.bin", "a_old.bin", "b_old.bin"]);
+        assert_eq!(selection.best.unwrap().path, PathBuf::from("new.bin"));
+
+        assert_eq!(selection.rejected.len(), 2);
+        assert!(selection.rejected.iter().all(|r| !r.reasons.is_empty()));
+        assert!(selection.rejected[0].reasons.iter().any(|r| r.contains("DRAM")));
+        assert!(selection.rejected[1].reasons.iter().any(|r| r.contains("颗粒")));
+    }
+
+    #[test]
+    fn select_without_compatible_image_has_no_best() {
+        let target = FirmwareCodec::parse_firmware_code(&code(2025, 6, 1, 1024, 4)).unwrap();
+        let selection = select(&target, vec![image("x.bin", &code(2025, 1, 1, -1, 4))]);
+        assert!(selection.best.is_none());
+        assert_eq!(selection.rejected.len(), 1);
+    }
+
+    #[test]
+    fn reads_ata_identify_words() {
+        let code = code(2025, 6, 1, 1024, 4);
+        let mut data = vec![0u8; 512];
+        // 字 23-26 中每个字的两个字节交换存放
+        for (i, pair) in code.as_bytes().chunks(2).enumerate() {
+            data[46 + i * 2] = pair[1];
+            data[46 + i * 2 + 1] = pair[0];
+        }
+        assert_eq!(firmware_code_from_identify(&data).unwrap(), code);
+    }
+
+    #[test]
+    fn reads_nvme_identify_bytes() {
+        let code = code(2025, 6, 1, 1024, 4);
+        let mut data = vec![b' '; 4096];
+        data[64..72].copy_from_slice(code.as_bytes());
+        assert_eq!(firmware_code_from_identify(&data).unwrap(), code);
+
+        data[64..72].copy_from_slice(b"NOTACODE");
+        assert!(firmware_code_from_identify(&data).is_err());
+        assert!(firmware_code_from_identify(&[0u8; 100]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_skips_symlinked_directories() {
+        let root = std::env::temp_dir().join(format!("ssd_sn_tool_fw_repo_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("sub/fw.bin"), b"").unwrap();
+        std::fs::write(root.join("notes.txt"), b"").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("sub/loop")).unwrap();
+
+        let files = collect_images(&root).unwrap();
+        assert_eq!(files, [root.join("sub/fw.bin")]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use crate::config::{dram_size_label, CONFIG};
+use crate::error::SnError;
+use crate::firmware_codec::{FirmwareCodec, FirmwareFields};
+
+// 默认历史记录文件名
+pub const DEFAULT_HISTORY: &str = "ssd_tool_history.yaml";
+
+// 默认历史记录文件：用户数据目录下的 ssd_sn_tool/ssd_tool_history.yaml，
+// 与启动时的工作目录无关；取不到数据目录时退回当前目录
+pub fn default_history_path() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("ssd_sn_tool").join(DEFAULT_HISTORY))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_HISTORY))
+}
+
+// 未固定记录的保留条数
+pub(crate) const MAX_UNPINNED: usize = 500;
+
+// 记录来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryAction {
+    Generated,
+    Parsed,
+}
+
+impl HistoryAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistoryAction::Generated => "generated",
+            HistoryAction::Parsed => "parsed",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HistoryAction::Generated => "生成",
+            HistoryAction::Parsed => "解析",
+        }
+    }
+}
+
+// 历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub code: String,
+    pub action: HistoryAction,
+    pub time: DateTime<Utc>,
+    // 解码摘要，如 "2025-08-01 / PCB 1 / 1GB / BGA132/152 / 4 颗"
+    pub summary: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+impl HistoryEntry {
+    pub fn new(code: &str, action: HistoryAction, time: DateTime<Utc>) -> Result<Self, SnError> {
+        let code = code.trim().to_ascii_uppercase();
+        let fields = FirmwareCodec::parse_firmware_code(&code)?;
+        Ok(Self {
+            code,
+            action,
+            time,
+            summary: summarize(&fields),
+            pinned: false,
+        })
+    }
+
+    // 按当前配置重新解析（配置变更后可能失败）
+    pub fn fields(&self) -> Option<FirmwareFields> {
+        FirmwareCodec::parse_firmware_code(&self.code).ok()
+    }
+}
+
+// 版本号字段的一行摘要
+pub fn summarize(fields: &FirmwareFields) -> String {
+    let (year, month, day, pcb_size, dram_size_mb, package_code, chip_count) = *fields;
+    format!(
+        "{}-{:02}-{:02} / PCB {} / {} / {} / {} 颗",
+        year, month, day, pcb_size, dram_size_label(dram_size_mb), CONFIG.firmware.get_package_name(package_code), chip_count
+    )
+}
+
+// 生成/解析历史（新记录在前）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    // 加载历史记录，文件不存在时返回空记录
+    pub fn load(path: &Path) -> Result<Self, SnError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SnError::InvalidParameter(format!("无法读取历史记录文件 {}: {}", path.display(), e)))?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| SnError::ConfigError(format!("历史记录文件格式错误: {}", e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SnError> {
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| SnError::ConfigError(format!("历史记录序列化失败: {}", e)))?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| SnError::InvalidParameter(format!("无法创建目录 {}: {}", dir.display(), e)))?;
+        }
+        std::fs::write(path, content)
+            .map_err(|e| SnError::InvalidParameter(format!("无法写入历史记录文件 {}: {}", path.display(), e)))
+    }
+
+    // 添加记录：已有相同版本号和来源的记录时移到最前并更新时间（保留固定状态）
+    // 最新一条已是该记录时不做改动，返回 false
+    pub fn record(&mut self, mut entry: HistoryEntry) -> bool {
+        let same = |e: &HistoryEntry| e.code == entry.code && e.action == entry.action;
+        if self.entries.first().is_some_and(same) {
+            return false;
+        }
+        if let Some(index) = self.entries.iter().position(same) {
+            entry.pinned = self.entries.remove(index).pinned;
+        }
+        self.entries.insert(0, entry);
+
+        // 超出保留条数时删除最旧的未固定记录
+        let mut unpinned = 0;
+        self.entries.retain(|e| {
+            if e.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= MAX_UNPINNED
+        });
+        true
+    }
+
+    pub fn toggle_pin(&mut self, index: usize) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.pinned = !entry.pinned;
+        }
+    }
+
+    // 删除所有未固定的记录
+    pub fn clear_unpinned(&mut self) {
+        self.entries.retain(|e| e.pinned);
+    }
+
+    // 按版本号或摘要搜索（大小写不敏感），固定的记录在前；返回下标
+    pub fn search(&self, text: &str) -> Vec<usize> {
+        let text = text.trim().to_lowercase();
+        let mut results: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| {
+                let entry = &self.entries[i];
+                text.is_empty()
+                    || entry.code.to_lowercase().contains(&text)
+                    || entry.summary.to_lowercase().contains(&text)
+            })
+            .collect();
+        results.sort_by_key(|&i| !self.entries[i].pinned);
+        results
+    }
+
+    // 导出为 CSV（时间为本地时间）
+    pub fn to_csv(&self, indices: &[usize]) -> String {
+        let mut out = String::from("time,action,code,summary,pinned\n");
+        for entry in indices.iter().filter_map(|&i| self.entries.get(i)) {
+            let _ = writeln!(
+                out,
+                "{},{},{},\"{}\",{}",
+                entry.time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                entry.action.name(),
+                entry.code,
+                entry.summary.replace('"', "\"\""),
+                entry.pinned
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(code: &str, action: HistoryAction, minute: u32) -> HistoryEntry {
+        HistoryEntry::new(code, action, Utc.with_ymd_and_hms(2025, 8, 1, 9, minute, 0).unwrap()).unwrap()
+    }
+
+    fn code(day: u32, chips: u8) -> String {
+        code_in(8, day, chips)
+    }
+
+    fn code_in(month: u32, day: u32, chips: u8) -> String {
+        FirmwareCodec::generate_firmware_code(2025, month, day, 1, 1024, 'A', chips).unwrap()
+    }
+
+    fn codes(history: &History) -> Vec<(String, HistoryAction)> {
+        history.entries.iter().map(|e| (e.code.clone(), e.action)).collect()
+    }
+
+    #[test]
+    fn record_moves_duplicates_to_front() {
+        let (a, b) = (code(1, 4), code(2, 4));
+        let mut history = History::default();
+        assert!(history.record(entry(&a, HistoryAction::Generated, 0)));
+        assert!(!history.record(entry(&a.to_ascii_lowercase(), HistoryAction::Generated, 1)));
+        assert!(history.record(entry(&a, HistoryAction::Parsed, 2)));
+        assert!(history.record(entry(&b, HistoryAction::Generated, 3)));
+        history.toggle_pin(2);
+
+        assert!(history.record(entry(&a, HistoryAction::Generated, 4)));
+        assert_eq!(codes(&history), [
+            (a.clone(), HistoryAction::Generated),
+            (b, HistoryAction::Generated),
+            (a, HistoryAction::Parsed),
+        ]);
+        // 移到最前时保留固定状态并更新时间
+        assert!(history.entries[0].pinned);
+        assert_eq!(history.entries[0].time, Utc.with_ymd_and_hms(2025, 8, 1, 9, 4, 0).unwrap());
+
+        history.toggle_pin(0);
+        assert!(!history.entries[0].pinned);
+    }
+
+    #[test]
+    fn trimming_keeps_pinned_entries() {
+        let mut history = History::default();
+        history.record(entry(&code(1, 1), HistoryAction::Generated, 0));
+        history.toggle_pin(0);
+        for i in 0..MAX_UNPINNED + 5 {
+            let (month, day) = (9 + (i / (16 * 28)) as u32, 1 + (i / 16 % 28) as u32);
+            history.record(entry(&code_in(month, day, 1 + (i % 16) as u8), HistoryAction::Generated, 0));
+        }
+        assert_eq!(history.entries.len(), MAX_UNPINNED + 1);
+        let pinned = history.entries.last().unwrap();
+        assert!(pinned.pinned && pinned.code == code(1, 1));
+
+        history.clear_unpinned();
+        assert_eq!(history.entries.len(), 1);
+    }
+
+    #[test]
+    fn search_matches_code_and_summary_with_pinned_first() {
+        let mut history = History::default();
+        history.record(entry(&code(1, 4), HistoryAction::Generated, 0));
+        history.record(entry(&code(2, 4), HistoryAction::Generated, 1));
+        history.record(entry(&code(3, 8), HistoryAction::Parsed, 2));
+        history.toggle_pin(2);
+
+        assert_eq!(history.search(""), [2, 0, 1]);
+        assert_eq!(history.search(&code(3, 8).to_ascii_lowercase()), [0]);
+        assert_eq!(history.search("2025-08-02"), [1]);
+        assert_eq!(history.search("4 颗"), [2, 1]);
+        assert!(history.search("no such code").is_empty());
+    }
+
+    #[test]
+    fn csv_quotes_summary_and_skips_missing_indices() {
+        let mut history = History::default();
+        history.record(entry(&code(1, 4), HistoryAction::Parsed, 0));
+        history.entries[0].summary = "say \"hi\", ok".to_string();
+        let csv = history.to_csv(&[0, 7]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "time,action,code,summary,pinned");
+        assert!(lines[1].ends_with(&format!(",parsed,{},\"say \"\"hi\"\", ok\",false", code(1, 4))));
+    }
+
+    #[test]
+    fn save_creates_parent_directory() {
+        let dir = std::env::temp_dir().join(format!("ssd_sn_tool_history_{}", std::process::id()));
+        let path = dir.join("nested").join(DEFAULT_HISTORY);
+        let mut history = History::default();
+        history.record(entry(&code(1, 4), HistoryAction::Generated, 0));
+        history.save(&path).unwrap();
+        assert_eq!(codes(&History::load(&path).unwrap()), codes(&history));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
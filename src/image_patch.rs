@@ -0,0 +1,339 @@
+use std::path::Path;
+use serde::Deserialize;
+use crate::error::SnError;
+use crate::firmware_codec::FirmwareCodec;
+use crate::firmware_scan;
+
+// 固件版本号在镜像中的长度
+const CODE_LEN: usize = 8;
+
+// 校验和类型
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumKind {
+    Crc32,
+    Sum8,
+    Sum16,
+}
+
+impl ChecksumKind {
+    // 校验值占用字节数
+    pub fn width(&self) -> usize {
+        match self {
+            ChecksumKind::Crc32 => 4,
+            ChecksumKind::Sum8 => 1,
+            ChecksumKind::Sum16 => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumKind::Crc32 => "crc32",
+            ChecksumKind::Sum8 => "sum8",
+            ChecksumKind::Sum16 => "sum16",
+        }
+    }
+}
+
+// 校验和定义：对 [start, end) 区域计算，写入 store_at
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChecksumSpec {
+    pub kind: ChecksumKind,
+    pub start: usize,
+    pub end: usize,
+    pub store_at: usize,
+    // 大端存储（默认小端）
+    #[serde(default)]
+    pub big_endian: bool,
+    // 存储补码，使区域与校验值之和为 0（仅 sum8/sum16）
+    #[serde(default)]
+    pub complement: bool,
+}
+
+// 镜像布局
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImageLayout {
+    // 固件版本号偏移，未指定时在镜像中搜索
+    #[serde(default)]
+    pub code_offset: Option<usize>,
+    #[serde(default)]
+    pub checksums: Vec<ChecksumSpec>,
+}
+
+impl ImageLayout {
+    pub fn load(path: &Path) -> Result<Self, SnError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SnError::InvalidParameter(format!("无法读取镜像布局文件 {}: {}", path.display(), e)))?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| SnError::ConfigError(format!("镜像布局文件格式错误: {}", e)))
+    }
+}
+
+// 校验和更新记录
+#[derive(Debug, Clone)]
+pub struct ChecksumUpdate {
+    pub kind: ChecksumKind,
+    pub store_at: usize,
+    pub old_value: u32,
+    pub new_value: u32,
+}
+
+// 修改结果
+#[derive(Debug, Clone)]
+pub struct PatchReport {
+    pub offset: usize,
+    pub old_code: String,
+    pub new_code: String,
+    pub checksums: Vec<ChecksumUpdate>,
+}
+
+// 定位镜像中的固件版本号（必须恰好出现一次）
+pub fn locate_code(data: &[u8], layout: &ImageLayout) -> Result<(usize, String), SnError> {
+    if let Some(offset) = layout.code_offset {
+        let window = offset.checked_add(CODE_LEN)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(|| SnError::SnFormatError(format!("偏移 0x{:X} 超出镜像范围", offset)))?;
+        let code = String::from_utf8_lossy(window).to_string();
+        FirmwareCodec::parse_firmware_code(&code)
+            .map_err(|e| SnError::SnFormatError(format!("偏移 0x{:X} 处不是有效的固件版本号: {}", offset, e)))?;
+        return Ok((offset, code));
+    }
+
+    let matches = firmware_scan::scan_codes(data);
+    match matches.as_slice() {
+        [m] => Ok((m.offset, m.code.clone())),
+        [] => Err(SnError::SnFormatError("镜像中未找到固件版本号".to_string())),
+        _ => {
+            let found: Vec<String> = matches.iter()
+                .map(|m| format!("{}@0x{:X}", m.code, m.offset))
+                .collect();
+            Err(SnError::SnFormatError(format!(
+                "镜像中找到 {} 个固件版本号，无法确定替换位置: {}",
+                matches.len(),
+                found.join(", ")
+            )))
+        }
+    }
+}
+
+// 替换固件版本号并重新计算校验和
+pub fn patch_image(data: &mut [u8], layout: &ImageLayout, new_code: &str) -> Result<PatchReport, SnError> {
+    FirmwareCodec::parse_firmware_code(new_code)?;
+    let (offset, old_code) = locate_code(data, layout)?;
+
+    // 先检查所有校验和定义，避免写入一半
+    for spec in &layout.checksums {
+        validate_spec(spec, data.len())?;
+    }
+
+    data[offset..offset + CODE_LEN].copy_from_slice(new_code.as_bytes());
+
+    let mut checksums = Vec::new();
+    for spec in &layout.checksums {
+        let old_value = read_value(data, spec);
+        let new_value = compute_checksum(&data[spec.start..spec.end], spec);
+        write_value(data, spec, new_value);
+        checksums.push(ChecksumUpdate {
+            kind: spec.kind,
+            store_at: spec.store_at,
+            old_value,
+            new_value,
+        });
+    }
+
+    Ok(PatchReport {
+        offset,
+        old_code,
+        new_code: new_code.to_string(),
+        checksums,
+    })
+}
+
+fn validate_spec(spec: &ChecksumSpec, len: usize) -> Result<(), SnError> {
+    if spec.start >= spec.end || spec.end > len {
+        return Err(SnError::ConfigError(format!(
+            "{} 校验区域 0x{:X}..0x{:X} 无效", spec.kind.name(), spec.start, spec.end
+        )));
+    }
+    // 布局文件中的偏移可能很大，相加时不能溢出
+    let store_end = match spec.store_at.checked_add(spec.kind.width()) {
+        Some(end) if end <= len => end,
+        _ => {
+            return Err(SnError::ConfigError(format!(
+                "{} 校验值位置 0x{:X} 超出镜像范围", spec.kind.name(), spec.store_at
+            )));
+        }
+    };
+    if spec.store_at < spec.end && store_end > spec.start {
+        return Err(SnError::ConfigError(format!(
+            "{} 校验值位置 0x{:X} 与校验区域重叠", spec.kind.name(), spec.store_at
+        )));
+    }
+    Ok(())
+}
+
+pub fn compute_checksum(region: &[u8], spec: &ChecksumSpec) -> u32 {
+    match spec.kind {
+        ChecksumKind::Crc32 => crc32(region),
+        ChecksumKind::Sum8 => {
+            let sum = region.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+            u32::from(if spec.complement { sum.wrapping_neg() } else { sum })
+        }
+        ChecksumKind::Sum16 => {
+            let sum = region.iter().fold(0u16, |acc, &b| acc.wrapping_add(u16::from(b)));
+            u32::from(if spec.complement { sum.wrapping_neg() } else { sum })
+        }
+    }
+}
+
+// CRC-32 (IEEE 802.3)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn read_value(data: &[u8], spec: &ChecksumSpec) -> u32 {
+    let bytes = &data[spec.store_at..spec.store_at + spec.kind.width()];
+    let iter: Box<dyn Iterator<Item = &u8>> = if spec.big_endian {
+        Box::new(bytes.iter())
+    } else {
+        Box::new(bytes.iter().rev())
+    };
+    iter.fold(0u32, |acc, &b| (acc << 8) | u32::from(b))
+}
+
+fn write_value(data: &mut [u8], spec: &ChecksumSpec, value: u32) {
+    let width = spec.kind.width();
+    let le = value.to_le_bytes();
+    for (i, slot) in data[spec.store_at..spec.store_at + width].iter_mut().enumerate() {
+        *slot = if spec.big_endian { le[width - 1 - i] } else { le[i] };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_code_offset_is_out_of_range() {
+        let layout = ImageLayout { code_offset: Some(usize::MAX - 2), checksums: Vec::new() };
+        let err = locate_code(&[0u8; 64], &layout).unwrap_err();
+        assert!(err.message().contains("超出镜像范围"));
+    }
+
+    #[test]
+    fn huge_store_offset_is_out_of_range() {
+        let spec = ChecksumSpec {
+            kind: ChecksumKind::Crc32,
+            start: 0,
+            end: 16,
+            store_at: usize::MAX - 1,
+            big_endian: false,
+            complement: false,
+        };
+        let err = validate_spec(&spec, 64).unwrap_err();
+        assert!(err.message().contains("超出镜像范围"));
+    }
+
+    fn spec(kind: ChecksumKind, store_at: usize, big_endian: bool, complement: bool) -> ChecksumSpec {
+        ChecksumSpec { kind, start: 0, end: 32, store_at, big_endian, complement }
+    }
+
+    fn image_with(code: &[u8], offset: usize) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[offset..offset + code.len()].copy_from_slice(code);
+        data
+    }
+
+    #[test]
+    fn crc32_matches_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn sums_with_and_without_complement() {
+        let region = [0xF0u8, 0x20, 0x01];
+        assert_eq!(compute_checksum(&region, &spec(ChecksumKind::Sum8, 40, false, false)), 0x11);
+        assert_eq!(compute_checksum(&region, &spec(ChecksumKind::Sum8, 40, false, true)), 0xEF);
+        assert_eq!(compute_checksum(&region, &spec(ChecksumKind::Sum16, 40, false, false)), 0x0111);
+        assert_eq!(compute_checksum(&region, &spec(ChecksumKind::Sum16, 40, false, true)), 0xFEEF);
+    }
+
+    #[test]
+    fn values_are_stored_in_configured_byte_order() {
+        let mut data = vec![0u8; 8];
+        let little = ChecksumSpec { store_at: 0, ..spec(ChecksumKind::Crc32, 0, false, false) };
+        let big = ChecksumSpec { store_at: 4, ..spec(ChecksumKind::Crc32, 0, true, false) };
+        write_value(&mut data, &little, 0x1234_5678);
+        write_value(&mut data, &big, 0x1234_5678);
+        assert_eq!(data, [0x78, 0x56, 0x34, 0x12, 0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(read_value(&data, &little), 0x1234_5678);
+        assert_eq!(read_value(&data, &big), 0x1234_5678);
+
+        let sum16 = spec(ChecksumKind::Sum16, 2, true, false);
+        write_value(&mut data, &sum16, 0xABCD);
+        assert_eq!(&data[..4], [0x78, 0x56, 0xAB, 0xCD]);
+        assert_eq!(read_value(&data, &sum16), 0xABCD);
+    }
+
+    #[test]
+    fn patch_replaces_code_and_updates_checksums() {
+        let mut data = image_with(b"S5ID13A4", 16);
+        let layout = ImageLayout {
+            code_offset: None,
+            checksums: vec![
+                spec(ChecksumKind::Crc32, 32, false, false),
+                spec(ChecksumKind::Sum8, 40, false, true),
+                spec(ChecksumKind::Sum16, 44, true, false),
+            ],
+        };
+        let report = patch_image(&mut data, &layout, "S5ID13A5").unwrap();
+        assert_eq!((report.offset, report.old_code.as_str()), (16, "S5ID13A4"));
+        assert_eq!(&data[16..24], b"S5ID13A5");
+
+        let region = &data[..32];
+        assert_eq!(read_value(&data, &layout.checksums[0]), crc32(region));
+        assert_eq!(report.checksums[0].old_value, 0);
+        assert_eq!(report.checksums[0].new_value, crc32(region));
+        // 补码使区域与校验值之和为 0
+        let sum8 = region.iter().fold(data[40], |acc, &b| acc.wrapping_add(b));
+        assert_eq!(sum8, 0);
+        let sum16 = region.iter().fold(0u16, |acc, &b| acc.wrapping_add(u16::from(b)));
+        assert_eq!(u16::from_be_bytes([data[44], data[45]]), sum16);
+    }
+
+    #[test]
+    fn refuses_missing_or_repeated_code() {
+        let layout = ImageLayout::default();
+        let mut empty = vec![0u8; 64];
+        assert!(patch_image(&mut empty, &layout, "S5ID13A5").is_err());
+
+        let mut twice = image_with(b"S5ID13A4", 8);
+        twice[32..40].copy_from_slice(b"S5ID13A4");
+        let before = twice.clone();
+        let err = patch_image(&mut twice, &layout, "S5ID13A5").unwrap_err();
+        assert!(err.message().contains("2 个"));
+        assert_eq!(twice, before);
+    }
+
+    #[test]
+    fn bad_spec_leaves_image_untouched() {
+        let mut data = image_with(b"S5ID13A4", 16);
+        let before = data.clone();
+        // 第二个定义的校验值位于校验区域内，必须在写入任何内容之前拒绝
+        let layout = ImageLayout {
+            code_offset: Some(16),
+            checksums: vec![spec(ChecksumKind::Crc32, 32, false, false), spec(ChecksumKind::Sum8, 4, false, false)],
+        };
+        let err = patch_image(&mut data, &layout, "S5ID13A5").unwrap_err();
+        assert!(err.message().contains("重叠"));
+        assert_eq!(data, before);
+    }
+}
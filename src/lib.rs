@@ -1,4 +1,38 @@
 pub mod config;
 pub mod firmware_codec;
+pub mod code_compare;
+pub mod code_pattern;
+pub mod code_segments;
+pub mod date_calendar;
+pub mod factory_clock;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(feature = "cli", feature = "gui"))]
+pub mod catalog;
+#[cfg(any(feature = "cli", feature = "gui"))]
+pub mod firmware_scan;
+#[cfg(any(feature = "cli", feature = "gui"))]
+pub mod preset;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod codegen;
+#[cfg(feature = "cli")]
+pub mod fw_organize;
+#[cfg(feature = "cli")]
+pub mod fw_repo;
+#[cfg(feature = "cli")]
+pub mod image_patch;
+#[cfg(feature = "cli")]
+pub mod server;
+#[cfg(feature = "cli")]
+pub mod stamp;
+#[cfg(feature = "gui")]
+pub mod code_extract;
+#[cfg(feature = "gui")]
+pub mod config_editor;
+#[cfg(feature = "gui")]
+pub mod history;
+#[cfg(feature = "gui")]
 pub mod ui;
\ No newline at end of file
@@ -1,23 +1,58 @@
 // src/main.rs
 
 // Windows 特定的代码：隐藏控制台窗口
-#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-
-mod config;
-mod firmware_codec;
-mod error;
-mod ui;
+#![cfg_attr(all(not(debug_assertions), feature = "gui"), windows_subsystem = "windows")]
 
+use ssd_sn_tool::config;
+#[cfg(feature = "cli")]
+use ssd_sn_tool::cli;
+#[cfg(feature = "gui")]
 use eframe::egui;
-use ui::SsdToolApp;
+#[cfg(feature = "gui")]
+use ssd_sn_tool::ui::SsdToolApp;
+
+fn main() {
+    // --config <文件> 必须放在最前，在首次使用配置前改用该文件
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("--config") {
+        let Some(path) = args.get(1).cloned() else {
+            eprintln!("--config 缺少配置文件路径");
+            std::process::exit(2);
+        };
+        if let Err(e) = config::use_config_file(std::path::Path::new(&path)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        args.drain(..2);
+    }
 
-fn main() -> Result<(), eframe::Error> {
     // 验证配置
     if let Err(e) = config::CONFIG.firmware.validate() {
         eprintln!("固件配置验证失败: {}", e);
         std::process::exit(1);
     }
 
+    // 带参数时进入命令行模式
+    #[cfg(feature = "cli")]
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
+    #[cfg(feature = "gui")]
+    if let Err(e) = run_gui() {
+        eprintln!("图形界面启动失败: {}", e);
+        std::process::exit(1);
+    }
+
+    #[cfg(not(feature = "gui"))]
+    {
+        ssd_sn_tool::firmware_codec::FirmwareCodec::print_usage();
+        std::process::exit(2);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_gui() -> Result<(), eframe::Error> {
     // 启动 GUI 应用
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -38,6 +73,7 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
+#[cfg(feature = "gui")]
 fn setup_fonts(ctx: &egui::Context) {
     let mut fonts = egui::FontDefinitions::default();
 
@@ -55,4 +91,4 @@ fn setup_fonts(ctx: &egui::Context) {
         .insert(0, "misans".to_owned());
 
     ctx.set_fonts(fonts);
-}
\ No newline at end of file
+}
@@ -0,0 +1,219 @@
+use std::path::Path;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use crate::config::{dram_size_label, FirmwareConfig, CONFIG};
+use crate::error::SnError;
+use crate::firmware_codec::{parse_dram_quantity, FirmwareCodec};
+
+// 默认预设文件
+pub const DEFAULT_PRESETS: &str = "hardware_presets.yaml";
+
+// 硬件预设（生成时只需再给出日期）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub pcb_size: u8,
+    // 与命令行相同的写法，如 1GB、512MB、DRAMLess
+    pub dram_size: String,
+    pub package_code: char,
+    pub chip_count: u8,
+    // 可选的用途说明，如固件配置名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl Preset {
+    // 按当前配置检查，返回不再有效的字段说明（为空表示有效）
+    pub fn problems(&self) -> Vec<String> {
+        self.problems_in(&CONFIG.firmware)
+    }
+
+    // 按指定配置检查
+    pub fn problems_in(&self, firmware: &FirmwareConfig) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !firmware.is_valid_pcb_size(self.pcb_size) {
+            problems.push(format!("PCB尺寸 {} 已不在配置中", self.pcb_size));
+        } else if firmware.is_retired_pcb_size(self.pcb_size) {
+            problems.push(format!("PCB尺寸 {} 已停用", self.pcb_size));
+        }
+        match parse_dram_quantity(&self.dram_size) {
+            Ok(size_mb) => match firmware.dram_sizes.iter().find(|&(_, &size)| size == size_mb) {
+                Some((&code, _)) if firmware.is_retired_dram_code(code) => {
+                    problems.push(format!("DRAM大小 {} 已停用", dram_size_label(size_mb)));
+                }
+                Some(_) => {}
+                None => problems.push(format!("DRAM大小 {} 已不在配置中", dram_size_label(size_mb))),
+            },
+            Err(e) => problems.push(e.message().to_string()),
+        }
+        if !firmware.is_valid_package(self.package_code) {
+            problems.push(format!("封装代码 {} 已不在配置中", self.package_code));
+        } else if firmware.is_retired_package(self.package_code) {
+            problems.push(format!("封装代码 {} 已停用", self.package_code));
+        }
+        if !firmware.is_valid_chip_count(self.chip_count) {
+            problems.push(format!(
+                "颗粒数量 {} 超出范围 ({}-{})", self.chip_count, firmware.chip_count.min, firmware.chip_count.max
+            ));
+        }
+        problems
+    }
+
+    pub fn dram_size_mb(&self) -> Result<i32, SnError> {
+        FirmwareCodec::parse_dram_size(&self.dram_size)
+    }
+
+    pub fn generate(&self, date: NaiveDate) -> Result<String, SnError> {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            return Err(SnError::ConfigError(format!("预设 {} 已失效: {}", self.name, problems.join("；"))));
+        }
+        FirmwareCodec::generate_firmware_code(
+            date.year(), date.month(), date.day(),
+            self.pcb_size, self.dram_size_mb()?, self.package_code, self.chip_count,
+        )
+    }
+
+    // 一行摘要，如 "PCB 1 / 1GB / B / 4 颗 [消费级]"
+    pub fn summary(&self) -> String {
+        let dram = self.dram_size_mb().map(dram_size_label).unwrap_or_else(|_| self.dram_size.clone());
+        let profile = self.profile.as_deref().map(|p| format!(" [{}]", p)).unwrap_or_default();
+        format!("PCB {} / {} / {} / {} 颗{}", self.pcb_size, dram, self.package_code, self.chip_count, profile)
+    }
+}
+
+// 预设文件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Presets {
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+}
+
+impl Presets {
+    // 加载预设并按当前配置重新校验，返回预设及失效预设的警告；文件不存在时返回空列表
+    pub fn load(path: &Path) -> Result<(Self, Vec<String>), SnError> {
+        if !path.exists() {
+            return Ok((Self::default(), Vec::new()));
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SnError::InvalidParameter(format!("无法读取预设文件 {}: {}", path.display(), e)))?;
+        let presets: Self = serde_yaml::from_str(&content)
+            .map_err(|e| SnError::ConfigError(format!("预设文件格式错误: {}", e)))?;
+        let warnings = presets.warnings();
+        Ok((presets, warnings))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SnError> {
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| SnError::ConfigError(format!("预设序列化失败: {}", e)))?;
+        std::fs::write(path, content)
+            .map_err(|e| SnError::InvalidParameter(format!("无法写入预设文件 {}: {}", path.display(), e)))
+    }
+
+    // 按名称查找（大小写不敏感）
+    pub fn find(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Preset, SnError> {
+        self.find(name).ok_or_else(|| {
+            let names: Vec<&str> = self.presets.iter().map(|p| p.name.as_str()).collect();
+            SnError::InvalidParameter(format!("没有名为 {} 的预设 (可选: {})", name, names.join(", ")))
+        })
+    }
+
+    // 添加或替换同名预设
+    pub fn upsert(&mut self, preset: Preset) {
+        match self.presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&preset.name)) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.presets.len();
+        self.presets.retain(|p| !p.name.eq_ignore_ascii_case(name.trim()));
+        self.presets.len() != len
+    }
+
+    // 所有失效预设的警告
+    pub fn warnings(&self) -> Vec<String> {
+        self.presets.iter()
+            .filter_map(|preset| {
+                let problems = preset.problems();
+                (!problems.is_empty()).then(|| format!("预设 {}: {}", preset.name, problems.join("；")))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(name: &str, dram_size: &str, package_code: char) -> Preset {
+        Preset {
+            name: name.to_string(),
+            pcb_size: 1,
+            dram_size: dram_size.to_string(),
+            package_code,
+            chip_count: 4,
+            profile: Some("消费级".to_string()),
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip_with_warnings() {
+        let dir = std::env::temp_dir().join(format!("ssd_sn_tool_presets_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DEFAULT_PRESETS);
+
+        let mut presets = Presets::default();
+        presets.upsert(preset("board-x", "1GB", 'B'));
+        presets.upsert(preset("stale", "1GB", 'Z'));
+        presets.save(&path).unwrap();
+
+        let (loaded, warnings) = Presets::load(&path).unwrap();
+        assert_eq!(loaded.presets, presets.presets);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("预设 stale:"));
+
+        let (empty, warnings) = Presets::load(&dir.join("missing.yaml")).unwrap();
+        assert!(empty.presets.is_empty() && warnings.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn upsert_replaces_by_name_and_remove_ignores_case() {
+        let mut presets = Presets::default();
+        presets.upsert(preset("Board-X", "1GB", 'B'));
+        presets.upsert(preset("board-y", "DRAMLess", 'A'));
+        presets.upsert(preset("board-x", "2GB", 'B'));
+        assert_eq!(presets.presets.len(), 2);
+        assert_eq!(presets.get("BOARD-X").unwrap().dram_size, "2GB");
+        assert!(presets.get("board-z").is_err());
+
+        assert!(presets.remove(" board-y "));
+        assert!(!presets.remove("board-y"));
+        assert_eq!(presets.presets.len(), 1);
+        assert!(presets.get("board-x").unwrap().summary().ends_with("[消费级]"));
+    }
+
+    #[test]
+    fn problems_report_retired_entries() {
+        let preset = preset("board-x", "1GB", 'B');
+        assert!(preset.problems().is_empty());
+        assert!(preset.generate(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()).is_ok());
+
+        let mut config = CONFIG.firmware.clone();
+        config.retired.packages.insert('B');
+        config.retired.dram_sizes.insert('3');
+        assert_eq!(preset.problems_in(&config), ["DRAM大小 1GB 已停用", "封装代码 B 已停用"]);
+
+        let missing = Preset { pcb_size: 200, chip_count: 99, ..preset.clone() };
+        let problems = missing.problems();
+        assert!(problems.iter().any(|p| p.contains("PCB尺寸 200 已不在配置中")));
+        assert!(problems.iter().any(|p| p.contains("颗粒数量 99 超出范围")));
+        assert!(missing.generate(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()).is_err());
+    }
+}
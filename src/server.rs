@@ -0,0 +1,337 @@
+use std::io::Read;
+use std::net::{SocketAddr, ToSocketAddrs};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Response, Server};
+use crate::config::CONFIG;
+use crate::error::SnError;
+use crate::firmware_codec::FirmwareCodec;
+
+// 请求体大小上限
+pub const MAX_BODY_LEN: usize = 64 * 1024;
+
+// 生成请求体
+#[derive(Debug, Deserialize)]
+pub struct GenerateRequest {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub pcb_size: u8,
+    pub dram_size_mb: i32,
+    pub package_code: char,
+    pub chip_count: u8,
+}
+
+// 生成结果
+#[derive(Debug, Serialize)]
+pub struct GenerateResponse {
+    pub code: String,
+}
+
+// 解析结果
+#[derive(Debug, Serialize)]
+pub struct ParseResponse {
+    pub code: String,
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub date: String,
+    pub pcb_size: u8,
+    pub pcb_desc: String,
+    pub dram_size_mb: i32,
+    pub dram_desc: String,
+    pub package_code: char,
+    pub package_desc: String,
+    pub chip_count: u8,
+}
+
+// 错误响应
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub kind: String,
+    pub message: String,
+}
+
+// 接口响应（状态码 + JSON 文本）
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl ApiResponse {
+    fn ok<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(500, "internal_error", &format!("序列化失败: {}", e)),
+        }
+    }
+
+    fn error(status: u16, kind: &str, message: &str) -> Self {
+        let body = ErrorResponse {
+            error: ErrorBody {
+                kind: kind.to_string(),
+                message: message.to_string(),
+            },
+        };
+        Self {
+            status,
+            body: serde_json::to_string(&body).unwrap_or_default(),
+        }
+    }
+
+    fn from_sn_error(e: &SnError) -> Self {
+        let status = match e {
+            SnError::ConfigError(_) => 500,
+            _ => 400,
+        };
+        Self::error(status, e.kind(), e.message())
+    }
+}
+
+// 处理单个请求（与网络层无关，便于直接调用）
+pub fn handle_request(method: &str, url: &str, body: &str) -> ApiResponse {
+    let path = url.split('?').next().unwrap_or("");
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        ("POST", ["firmware", "generate"]) => generate(body),
+        ("GET", ["firmware", "parse", code]) => parse(code),
+        ("GET", ["config"]) => ApiResponse::ok(&CONFIG.firmware),
+        ("GET", ["config", "limits"]) => match CONFIG.firmware.limits() {
+            Ok(limits) => ApiResponse::ok(&limits),
+            Err(e) => ApiResponse::from_sn_error(&e),
+        },
+        (_, ["firmware", "generate"]) | (_, ["firmware", "parse", _]) | (_, ["config"]) | (_, ["config", "limits"]) => {
+            ApiResponse::error(405, "method_not_allowed", "不支持的请求方法")
+        }
+        _ => ApiResponse::error(404, "not_found", "未知的接口路径"),
+    }
+}
+
+fn generate(body: &str) -> ApiResponse {
+    let req: GenerateRequest = match serde_json::from_str(body) {
+        Ok(req) => req,
+        Err(e) => {
+            let err = SnError::InvalidParameter(format!("请求体解析失败: {}", e));
+            return ApiResponse::from_sn_error(&err);
+        }
+    };
+
+    match FirmwareCodec::generate_firmware_code(
+        req.year, req.month, req.day, req.pcb_size, req.dram_size_mb, req.package_code, req.chip_count
+    ) {
+        Ok(code) => ApiResponse::ok(&GenerateResponse { code }),
+        Err(e) => ApiResponse::from_sn_error(&e),
+    }
+}
+
+fn parse(code: &str) -> ApiResponse {
+    let code = code.to_ascii_uppercase();
+    match FirmwareCodec::parse_firmware_code(&code) {
+        Ok((year, month, day, pcb_size, dram_size_mb, package_code, chip_count)) => {
+            let dram_code = FirmwareCodec::dram_size_to_code(dram_size_mb).unwrap_or('?');
+            ApiResponse::ok(&ParseResponse {
+                code,
+                year,
+                month,
+                day,
+                date: format!("{}-{:02}-{:02}", year, month, day),
+                pcb_size,
+                pcb_desc: CONFIG.firmware.get_pcb_size_name(pcb_size),
+                dram_size_mb,
+                dram_desc: CONFIG.firmware.get_dram_size_desc(dram_code),
+                package_code: package_code.to_ascii_uppercase(),
+                package_desc: CONFIG.firmware.get_package_name(package_code),
+                chip_count,
+            })
+        }
+        Err(e) => ApiResponse::from_sn_error(&e),
+    }
+}
+
+// 读取请求体：声明的 Content-Length 或实际内容超过上限时返回 413
+fn read_body(reader: &mut dyn Read, content_length: Option<usize>) -> Result<String, ApiResponse> {
+    let too_large = || ApiResponse::error(413, "payload_too_large", &format!("请求体超过 {} 字节", MAX_BODY_LEN));
+    if content_length.is_some_and(|len| len > MAX_BODY_LEN) {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    reader.take(MAX_BODY_LEN as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| ApiResponse::error(400, "invalid_parameter", &format!("读取请求体失败: {}", e)))?;
+    if body.len() > MAX_BODY_LEN {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+// 本地 HTTP 服务
+pub struct ApiServer {
+    server: Server,
+}
+
+impl ApiServer {
+    // 服务没有认证，默认只允许监听本机回环地址；allow_remote 为 true 时才允许其他地址
+    pub fn bind(addr: &str, allow_remote: bool) -> Result<Self, SnError> {
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs()
+            .map_err(|e| SnError::InvalidParameter(format!("无效的监听地址 {}: {}", addr, e)))?
+            .collect();
+        if !allow_remote && addrs.iter().any(|a| !a.ip().is_loopback()) {
+            return Err(SnError::InvalidParameter(format!(
+                "监听地址 {} 不是本机回环地址，服务没有认证，会暴露给网络中的其他主机；确有需要请加 --allow-remote", addr
+            )));
+        }
+        let server = Server::http(addr)
+            .map_err(|e| SnError::InvalidParameter(format!("无法监听地址 {}: {}", addr, e)))?;
+        Ok(Self { server })
+    }
+
+    // 实际监听地址（绑定端口 0 时用于获取分配的端口）
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    pub fn run(&self) {
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json; charset=utf-8"[..])
+            .expect("无效的响应头");
+
+        for mut request in self.server.incoming_requests() {
+            let content_length = request.body_length();
+            let response = match read_body(request.as_reader(), content_length) {
+                Ok(body) => handle_request(request.method().as_str(), request.url(), &body),
+                Err(response) => response,
+            };
+
+            let http_response = Response::from_string(response.body)
+                .with_status_code(response.status)
+                .with_header(content_type.clone());
+            if let Err(e) = request.respond(http_response) {
+                eprintln!("响应发送失败: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn json(response: &ApiResponse) -> Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    fn error_kind(response: &ApiResponse) -> String {
+        json(response)["error"]["kind"].as_str().unwrap_or_default().to_string()
+    }
+
+    const GENERATE_BODY: &str = r#"{"year":2025,"month":12,"day":1,"pcb_size":1,"dram_size_mb":1024,"package_code":"A","chip_count":4}"#;
+
+    #[test]
+    fn generate_returns_code() {
+        let response = handle_request("POST", "/firmware/generate", GENERATE_BODY);
+        assert_eq!(response.status, 200);
+        let expected = FirmwareCodec::generate_firmware_code(2025, 12, 1, 1, 1024, 'A', 4).unwrap();
+        assert_eq!(json(&response)["code"], expected);
+    }
+
+    #[test]
+    fn generate_rejects_bad_requests() {
+        let response = handle_request("POST", "/firmware/generate", "{not json");
+        assert_eq!((response.status, error_kind(&response).as_str()), (400, "invalid_parameter"));
+
+        let body = GENERATE_BODY.replace(r#""chip_count":4"#, r#""chip_count":99"#);
+        let response = handle_request("POST", "/firmware/generate", &body);
+        assert_eq!(response.status, 400);
+
+        let body = GENERATE_BODY.replace("1024", "3000");
+        let response = handle_request("POST", "/firmware/generate", &body);
+        assert_eq!(response.status, 400);
+        assert!(json(&response)["error"]["message"].as_str().unwrap().contains("GB"));
+    }
+
+    #[test]
+    fn parse_round_trips_generated_code() {
+        let code = FirmwareCodec::generate_firmware_code(2025, 12, 1, 1, 1024, 'A', 4).unwrap();
+        let response = handle_request("GET", &format!("/firmware/parse/{}?verbose=1", code.to_ascii_lowercase()), "");
+        assert_eq!(response.status, 200);
+        let value = json(&response);
+        assert_eq!(value["code"], code);
+        assert_eq!(value["date"], "2025-12-01");
+        assert_eq!(value["dram_size_mb"], 1024);
+        assert_eq!(value["package_code"], "A");
+        assert_eq!(value["chip_count"], 4);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_codes() {
+        for code in ["S123", "X5EJ13B4", "S5EJ1ZB4"] {
+            let response = handle_request("GET", &format!("/firmware/parse/{}", code), "");
+            assert_eq!(response.status, 400, "{}", code);
+            assert!(!error_kind(&response).is_empty());
+        }
+    }
+
+    #[test]
+    fn config_and_limits() {
+        let response = handle_request("GET", "/config", "");
+        assert_eq!(response.status, 200);
+        assert_eq!(json(&response)["base32_chars"], CONFIG.firmware.base32_chars);
+
+        let response = handle_request("GET", "/config/limits", "");
+        assert_eq!(response.status, 200);
+        let value = json(&response);
+        assert_eq!(value["chip_min"], CONFIG.firmware.chip_count.min);
+        assert_eq!(value["chip_max"], CONFIG.firmware.chip_count.max);
+    }
+
+    #[test]
+    fn unknown_paths_and_methods() {
+        let response = handle_request("GET", "/firmware/unknown", "");
+        assert_eq!((response.status, error_kind(&response).as_str()), (404, "not_found"));
+        let response = handle_request("GET", "/firmware/generate", "");
+        assert_eq!((response.status, error_kind(&response).as_str()), (405, "method_not_allowed"));
+        let response = handle_request("DELETE", "/config", "");
+        assert_eq!(response.status, 405);
+    }
+
+    #[test]
+    fn body_size_is_capped() {
+        let small = b"{}".to_vec();
+        assert_eq!(read_body(&mut small.as_slice(), Some(2)).ok().as_deref(), Some("{}"));
+
+        let large = vec![b' '; MAX_BODY_LEN + 1];
+        let declared = read_body(&mut [].as_slice(), Some(large.len())).err().unwrap();
+        assert_eq!(declared.status, 413);
+        // 未声明长度（分块传输）时按实际读取的内容判断
+        let chunked = read_body(&mut large.as_slice(), None).err().unwrap();
+        assert_eq!(chunked.status, 413);
+    }
+
+    #[test]
+    fn bind_requires_flag_for_non_loopback() {
+        assert!(ApiServer::bind("0.0.0.0:0", false).is_err());
+        assert!(ApiServer::bind("127.0.0.1:0", false).is_ok());
+    }
+
+    #[test]
+    fn serves_requests_over_http() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        let server = ApiServer::bind("127.0.0.1:0", false).unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /config/limits HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("application/json"));
+    }
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+use chrono::{DateTime, Datelike};
+use serde::Deserialize;
+use crate::error::SnError;
+use crate::factory_clock::{self, Clock, FixedClock, SystemClock};
+use crate::firmware_codec::{self, FirmwareCodec};
+
+// 板卡描述文件（YAML 或 TOML）
+#[derive(Debug, Clone, Deserialize)]
+pub struct BoardDescriptor {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub pcb_size: u8,
+    // 可写为 MB 数值或 "1GB"、"DRAMLess" 等
+    #[serde(alias = "dram_size", deserialize_with = "firmware_codec::deserialize_dram_size")]
+    pub dram_size_mb: i32,
+    pub package_code: char,
+    pub chip_count: u8,
+}
+
+impl BoardDescriptor {
+    pub fn load(path: &Path) -> Result<Self, SnError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SnError::InvalidParameter(format!("无法读取板卡描述文件 {}: {}", path.display(), e)))?;
+
+        let is_toml = path.extension()
+            .map(|ext| ext.eq_ignore_ascii_case("toml"))
+            .unwrap_or(false);
+
+        if is_toml {
+            toml::from_str(&content)
+                .map_err(|e| SnError::InvalidParameter(format!("板卡描述文件格式错误: {}", e)))
+        } else {
+            serde_yaml::from_str(&content)
+                .map_err(|e| SnError::InvalidParameter(format!("板卡描述文件格式错误: {}", e)))
+        }
+    }
+
+    pub fn generate(&self, year: i32, month: u32, day: u32) -> Result<String, SnError> {
+        FirmwareCodec::generate_firmware_code(
+            year, month, day, self.pcb_size, self.dram_size_mb, self.package_code, self.chip_count
+        )
+    }
+}
+
+// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StampFormat {
+    CHeader,
+    Env,
+    Rust,
+}
+
+impl StampFormat {
+    pub fn from_name(name: &str) -> Result<Self, SnError> {
+        match name.to_ascii_lowercase().as_str() {
+            "c" | "h" | "header" => Ok(StampFormat::CHeader),
+            "env" => Ok(StampFormat::Env),
+            "rust" | "rs" => Ok(StampFormat::Rust),
+            _ => Err(SnError::InvalidParameter(format!("不支持的输出格式: {} (可选 c|env|rust)", name))),
+        }
+    }
+
+    pub fn default_name(&self) -> &'static str {
+        match self {
+            StampFormat::CHeader | StampFormat::Env => "SSD_FW_VERSION",
+            StampFormat::Rust => "FW_VERSION",
+        }
+    }
+}
+
+// 解析版本号日期：显式指定（YYYY-MM-DD 或 today）> SOURCE_DATE_EPOCH > 当前生产日期
+pub fn resolve_stamp_date(explicit: Option<&str>) -> Result<(i32, u32, u32), SnError> {
+    let epoch = std::env::var("SOURCE_DATE_EPOCH").ok();
+    let clock = stamp_clock(epoch.as_deref())?;
+    let date = match explicit {
+        Some(date) => factory_clock::resolve_date(date, clock.as_ref())?,
+        None => factory_clock::today(clock.as_ref())?,
+    };
+    Ok((date.year(), date.month(), date.day()))
+}
+
+// 版本号写入构建时的时钟：设置了 SOURCE_DATE_EPOCH 时使用该时间（可复现构建），否则使用系统时钟
+fn stamp_clock(epoch: Option<&str>) -> Result<Box<dyn Clock>, SnError> {
+    match epoch {
+        Some(epoch) => {
+            let secs: i64 = epoch.trim().parse()
+                .map_err(|_| SnError::InvalidParameter(format!("无效的 SOURCE_DATE_EPOCH: {}", epoch)))?;
+            let datetime = DateTime::from_timestamp(secs, 0)
+                .ok_or_else(|| SnError::InvalidParameter(format!("SOURCE_DATE_EPOCH 超出范围: {}", epoch)))?;
+            Ok(Box::new(FixedClock(datetime)))
+        }
+        None => Ok(Box::new(SystemClock)),
+    }
+}
+
+// 输出名称会写入生成的 C/Rust 源码或环境变量，须为合法标识符 [A-Za-z_][A-Za-z0-9_]*
+pub fn validate_name(name: &str) -> Result<(), SnError> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SnError::InvalidParameter(format!("无效的名称 (应为字母或下划线开头的标识符): {}", name)))
+    }
+}
+
+// 生成写入固件构建的内容
+pub fn render_stamp(code: &str, format: StampFormat, name: &str, board: &BoardDescriptor) -> Result<String, SnError> {
+    validate_name(name)?;
+    let board_name = board.name.as_deref().unwrap_or("-");
+    Ok(match format {
+        StampFormat::CHeader => {
+            let guard = format!("{}_H", name.to_ascii_uppercase());
+            format!(
+                "/* 由 ssd_sn_tool firmware stamp 生成，请勿手动修改 (板卡: {}) */\n#ifndef {}\n#define {}\n\n#define {} \"{}\"\n\n#endif /* {} */\n",
+                board_name, guard, guard, name, code, guard
+            )
+        }
+        StampFormat::Env => format!("{}={}\n", name, code),
+        StampFormat::Rust => format!(
+            "// 由 ssd_sn_tool firmware stamp 生成，请勿手动修改 (板卡: {})\npub const {}: &str = \"{}\";\n",
+            board_name, name, code
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use crate::config::FactoryConfig;
+
+    fn board() -> BoardDescriptor {
+        BoardDescriptor { name: Some("E18".to_string()), pcb_size: 1, dram_size_mb: 1024, package_code: 'A', chip_count: 4 }
+    }
+
+    #[test]
+    fn source_date_epoch_fixes_the_clock() {
+        // 2025-08-01 17:00:00 UTC，即 +08:00 工厂的 2025-08-02
+        let clock = stamp_clock(Some(" 1754067600\n")).unwrap();
+        assert_eq!(clock.now(), Utc.with_ymd_and_hms(2025, 8, 1, 17, 0, 0).unwrap());
+
+        let factory = FactoryConfig { utc_offset: Some("+08:00".to_string()), day_start_hour: 0 };
+        assert_eq!(factory_clock::today_in(clock.as_ref(), &factory).unwrap(), NaiveDate::from_ymd_opt(2025, 8, 2).unwrap());
+        assert_eq!(
+            factory_clock::resolve_date("2024-02-29", clock.as_ref()).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_source_date_epoch_is_rejected() {
+        for epoch in ["", "yesterday", "1.5", "99999999999999999"] {
+            let err = stamp_clock(Some(epoch)).err().unwrap();
+            assert!(err.message().contains("SOURCE_DATE_EPOCH"), "{}", epoch);
+        }
+    }
+
+    #[test]
+    fn names_must_be_identifiers() {
+        for name in ["SSD_FW_VERSION", "_v2", "a"] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "2FW", "FW-VERSION", "FW VERSION", "FW\"", "版本"] {
+            assert_eq!(validate_name(name).unwrap_err().kind(), "invalid_parameter", "{}", name);
+        }
+        assert!(render_stamp("X", StampFormat::Rust, "bad name", &board()).is_err());
+    }
+
+    #[test]
+    fn renders_each_format() {
+        let board = board();
+        let code = board.generate(2025, 8, 1).unwrap();
+
+        let header = render_stamp(&code, StampFormat::CHeader, "fw_ver", &board).unwrap();
+        assert!(header.contains("#ifndef FW_VER_H\n#define FW_VER_H\n"));
+        assert!(header.contains(&format!("#define fw_ver \"{}\"\n", code)));
+        assert!(header.contains("(板卡: E18)"));
+
+        assert_eq!(render_stamp(&code, StampFormat::Env, "SSD_FW_VERSION", &board).unwrap(), format!("SSD_FW_VERSION={}\n", code));
+
+        let rust = render_stamp(&code, StampFormat::Rust, StampFormat::Rust.default_name(), &board).unwrap();
+        assert!(rust.ends_with(&format!("pub const FW_VERSION: &str = \"{}\";\n", code)));
+    }
+
+    #[test]
+    fn format_names() {
+        assert_eq!(StampFormat::from_name("H").unwrap(), StampFormat::CHeader);
+        assert_eq!(StampFormat::from_name("rs").unwrap(), StampFormat::Rust);
+        assert!(StampFormat::from_name("json").is_err());
+    }
+}
@@ -1,5 +1,17 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use chrono::{Datelike, Local, NaiveDate};
+use ssd_fw_core::FIRMWARE_CODE_LEN;
+use crate::catalog::{self, Catalog, CatalogQuery, CatalogRecord, ReleaseStatus};
+use crate::code_compare::{Comparison, Verdict};
+use crate::code_extract::{self, ExtractedCode, SortColumn};
+use crate::code_segments::{self, CodeField, SegmentState};
 use crate::firmware_codec::FirmwareCodec;
-use crate::config::CONFIG;
+use crate::config::{self, dram_size_label, CodecLimits, CONFIG};
+use crate::config_editor::{self, ConfigEditor, DiffLine};
+use crate::factory_clock::{self, Clock, SystemClock};
+use crate::history::{self, History, HistoryAction, HistoryEntry};
+use crate::preset::{self, Preset, Presets};
 
 // 应用状态
 pub struct SsdToolApp {
@@ -12,76 +24,305 @@ pub struct SsdToolApp {
     // 固件版本号解析状态
     firmware_parse: FirmwareParseState,
 
+    // 发布目录状态
+    catalog: CatalogState,
+
+    // 版本号比较状态
+    compare: CompareState,
+
+    // 文本提取状态
+    extract: ExtractState,
+
+    // 历史记录状态
+    history: HistoryState,
+
+    // 硬件预设状态
+    presets: PresetState,
+
+    // 配置编辑状态
+    config_editor: ConfigEditorState,
+
     // 错误信息
     error_message: Option<String>,
 
     // 成功信息
     success_message: Option<String>,
+
+    // 时钟（用于"今天"）
+    clock: Box<dyn Clock>,
 }
 
 // 标签页枚举
 #[derive(PartialEq)]
 pub enum Tab {
     Firmware,
+    Compare,
+    Extract,
+    Catalog,
+    Config,
 }
 
 // 固件版本号生成状态
 pub struct FirmwareGenerateState {
-    pub year: String,
-    pub month: String,
-    pub day: String,
+    pub date: NaiveDate,
     pub pcb_size: String,
     pub dram_size: String,
     pub package_code: String,
-    pub chip_count: String,
+    pub chip_count: u8,
     pub generated_code: String,
 }
 
+// 各字段的校验（错误信息用于表单内提示）
+impl FirmwareGenerateState {
+    fn checked_date(&self, limits: &CodecLimits) -> Result<NaiveDate, String> {
+        if limits.contains_date(self.date) {
+            Ok(self.date)
+        } else {
+            Err(format!("日期超出可编码范围 ({})", limits.date_range_desc()))
+        }
+    }
+
+    fn checked_pcb_size(&self) -> Result<u8, String> {
+        match self.pcb_size.parse::<u8>() {
+            Ok(size) if CONFIG.firmware.is_retired_pcb_size(size) => Err("该PCB尺寸已停用".to_string()),
+            Ok(size) if CONFIG.firmware.is_valid_pcb_size(size) => Ok(size),
+            _ => Err("无效的PCB尺寸".to_string()),
+        }
+    }
+
+    fn checked_dram_size(&self) -> Result<i32, String> {
+        let size_mb = FirmwareCodec::parse_dram_size(&self.dram_size).map_err(|e| e.message().to_string())?;
+        match FirmwareCodec::dram_size_to_code(size_mb) {
+            Ok(code) if CONFIG.firmware.is_retired_dram_code(code) => Err("该DRAM大小已停用".to_string()),
+            _ => Ok(size_mb),
+        }
+    }
+
+    fn checked_package_code(&self) -> Result<char, String> {
+        match self.package_code.chars().next() {
+            Some(code) if CONFIG.firmware.is_retired_package(code) => Err("该封装已停用".to_string()),
+            Some(code) if CONFIG.firmware.is_valid_package(code) => Ok(code),
+            Some(_) => Err("无效的封装代码".to_string()),
+            None => Err("请选择封装类型".to_string()),
+        }
+    }
+
+    fn checked_chip_count(&self, limits: &CodecLimits) -> Result<u8, String> {
+        if limits.chip_range().contains(&self.chip_count) {
+            Ok(self.chip_count)
+        } else {
+            Err(format!("颗粒数量必须在{}-{}之间", limits.chip_min, limits.chip_max))
+        }
+    }
+
+    // 所有字段有效时返回版本号，否则返回第一个错误
+    pub fn preview(&self, limits: &CodecLimits) -> Result<String, String> {
+        let date = self.checked_date(limits)?;
+        let pcb_size = self.checked_pcb_size()?;
+        let dram_size_mb = self.checked_dram_size()?;
+        let package_code = self.checked_package_code()?;
+        let chip_count = self.checked_chip_count(limits)?;
+        FirmwareCodec::generate_firmware_code(
+            date.year(), date.month(), date.day(), pcb_size, dram_size_mb, package_code, chip_count
+        ).map_err(|e| e.message().to_string())
+    }
+}
+
 // 固件版本号解析状态
 pub struct FirmwareParseState {
     pub firmware_code: String,
-    pub parsed_result: Option<ParsedFirmware>,
+    // 最近一次记入历史的版本号（输入变化后才再次记录）
+    pub last_recorded: String,
 }
 
-// 解析结果
-pub struct ParsedFirmware {
-    pub year: i32,
-    pub month: u32,
-    pub day: u32,
-    pub pcb_size: u8,
-    pub dram_size_mb: i32,
-    pub package_code: char,
-    pub chip_count: u8,
+// 发布目录状态
+pub struct CatalogState {
+    pub path: String,
+    pub catalog: Option<Catalog>,
+    // 加载失败后不再自动重试，等待手动加载
+    pub load_failed: bool,
+    pub dirty: bool,
+    // 查询条件
+    pub pcb_size: Option<u8>,
+    pub dram_size_mb: Option<i32>,
+    pub package_code: Option<char>,
+    pub chip_count: String,
+    pub status: Option<ReleaseStatus>,
+    pub from: String,
+    pub to: String,
+    pub latest: bool,
+    // 新增记录
+    pub new_code: String,
+    pub new_status: ReleaseStatus,
+    pub new_notes: String,
+}
+
+// 版本号比较状态
+pub struct CompareState {
+    // 待比较的版本号（每行一个，也可用空格或逗号分隔）
+    pub input: String,
+}
+
+// 文本提取状态
+pub struct ExtractState {
+    // 粘贴或拖入的文本
+    pub text: String,
+    pub rows: Vec<ExtractedCode>,
+    pub filter: String,
+    pub only_errors: bool,
+    pub sort: SortColumn,
+    pub ascending: bool,
+    pub export_path: String,
+}
+
+// 历史记录状态
+pub struct HistoryState {
+    pub path: String,
+    pub history: History,
+    pub search: String,
+    pub export_path: String,
+}
+
+// 硬件预设状态
+pub struct PresetState {
+    pub path: String,
+    pub presets: Presets,
+    pub selected: String,
+    // 保存当前表单为预设
+    pub new_name: String,
+    pub new_profile: String,
+}
+
+// 配置编辑状态
+pub struct ConfigEditorState {
+    pub path: String,
+    pub editor: Option<ConfigEditor>,
+    // 加载失败后不再自动重试，等待手动加载
+    pub load_failed: bool,
+    pub show_diff: bool,
+    // 新增或修改的条目
+    pub new_pcb_size: String,
+    pub new_pcb_name: String,
+    pub new_dram_code: String,
+    pub new_dram_size: String,
+    pub new_package_code: String,
+    pub new_package_name: String,
 }
 
 impl Default for SsdToolApp {
     fn default() -> Self {
-        Self {
+        Self::with_clock(Box::new(SystemClock))
+    }
+}
+
+impl SsdToolApp {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 使用指定时钟创建，生产日期默认为该时钟下的今天
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
+        let mut app = Self {
             active_tab: Tab::Firmware,
             firmware_generate: FirmwareGenerateState {
-                year: "2025".to_string(),
-                month: "12".to_string(),
-                day: "1".to_string(),
+                date: NaiveDate::default(),
                 pcb_size: "1".to_string(),
-                dram_size: "1024".to_string(),
+                dram_size: "1GB".to_string(),
                 package_code: "A".to_string(),
-                chip_count: "4".to_string(),
+                chip_count: 4,
                 generated_code: String::new(),
             },
             firmware_parse: FirmwareParseState {
                 firmware_code: String::new(),
-                parsed_result: None,
+                last_recorded: String::new(),
+            },
+            catalog: CatalogState {
+                path: catalog::DEFAULT_CATALOG.to_string(),
+                catalog: None,
+                load_failed: false,
+                dirty: false,
+                pcb_size: None,
+                dram_size_mb: None,
+                package_code: None,
+                chip_count: String::new(),
+                status: None,
+                from: String::new(),
+                to: String::new(),
+                latest: false,
+                new_code: String::new(),
+                new_status: ReleaseStatus::Beta,
+                new_notes: String::new(),
+            },
+            compare: CompareState {
+                input: String::new(),
+            },
+            extract: ExtractState {
+                text: String::new(),
+                rows: Vec::new(),
+                filter: String::new(),
+                only_errors: false,
+                sort: SortColumn::Line,
+                ascending: true,
+                export_path: "extracted_codes.csv".to_string(),
+            },
+            history: HistoryState {
+                path: history::default_history_path().display().to_string(),
+                history: History::default(),
+                search: String::new(),
+                export_path: "history_export.csv".to_string(),
+            },
+            presets: PresetState {
+                path: preset::DEFAULT_PRESETS.to_string(),
+                presets: Presets::default(),
+                selected: String::new(),
+                new_name: String::new(),
+                new_profile: String::new(),
+            },
+            config_editor: ConfigEditorState {
+                path: config_editor::default_config_path().display().to_string(),
+                editor: None,
+                load_failed: false,
+                show_diff: false,
+                new_pcb_size: String::new(),
+                new_pcb_name: String::new(),
+                new_dram_code: String::new(),
+                new_dram_size: String::new(),
+                new_package_code: String::new(),
+                new_package_name: String::new(),
             },
             error_message: None,
             success_message: None,
+            clock,
+        };
+        app.set_today();
+        match History::load(Path::new(&app.history.path)) {
+            Ok(history) => app.history.history = history,
+            Err(e) => app.error_message = Some(format!("无法加载历史记录: {}", e)),
+        }
+        match Presets::load(Path::new(&app.presets.path)) {
+            Ok((presets, warnings)) => {
+                if !warnings.is_empty() {
+                    app.error_message = Some(format!("以下预设引用了已停用的编码表项:\n{}", warnings.join("\n")));
+                }
+                app.presets.selected = presets.presets.first().map(|p| p.name.clone()).unwrap_or_default();
+                app.presets.presets = presets;
+            }
+            Err(e) => app.error_message = Some(format!("无法加载预设: {}", e)),
         }
+        app
     }
-}
 
-impl SsdToolApp {
-    #[allow(dead_code)]
-    pub fn new() -> Self {
-        Self::default()
+    // 生产日期设为当前生产日期（按工厂时区和生产日开始时刻）
+    pub fn set_today(&mut self) {
+        match factory_clock::today(self.clock.as_ref()) {
+            Ok(today) => {
+                self.firmware_generate.date = today;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("无法确定当前生产日期: {}", e));
+            }
+        }
     }
 
     // 生成固件版本号
@@ -90,134 +331,286 @@ impl SsdToolApp {
         self.error_message = None;
         self.success_message = None;
 
-        // 解析输入参数
-        let year = match self.firmware_generate.year.parse::<i32>() {
-            Ok(year) if year >= 2010 && year <= 2099 => year,
-            Ok(_) => {
-                self.error_message = Some("年份必须在2010-2099之间".to_string());
-                return;
-            }
-            Err(_) => {
-                self.error_message = Some("无效的年份".to_string());
+        let limits = match CONFIG.firmware.limits() {
+            Ok(limits) => limits,
+            Err(e) => {
+                self.error_message = Some(format!("配置错误: {}", e));
                 return;
             }
         };
 
-        let month = match self.firmware_generate.month.parse::<u32>() {
-            Ok(month) if month >= 1 && month <= 12 => month,
-            Ok(_) => {
-                self.error_message = Some("月份必须在1-12之间".to_string());
-                return;
+        match self.firmware_generate.preview(&limits) {
+            Ok(code) => {
+                self.record_history(&code, HistoryAction::Generated);
+                self.firmware_generate.generated_code = code;
+                self.success_message = Some("固件版本号生成成功！".to_string());
             }
-            Err(_) => {
-                self.error_message = Some("无效的月份".to_string());
-                return;
+            Err(e) => {
+                self.error_message = Some(format!("生成失败: {}", e));
             }
-        };
+        }
+    }
 
-        let day = match self.firmware_generate.day.parse::<u32>() {
-            Ok(day) if day >= 1 && day <= 31 => day,
-            Ok(_) => {
-                self.error_message = Some("日期必须在1-31之间".to_string());
-                return;
-            }
-            Err(_) => {
-                self.error_message = Some("无效的日期".to_string());
-                return;
-            }
+    // 将预设填入生成表单（日期不变）
+    pub fn apply_preset(&mut self, name: &str) -> bool {
+        let Some(preset) = self.presets.presets.find(name).cloned() else {
+            self.error_message = Some(format!("没有名为 {} 的预设", name));
+            return false;
         };
+        let generate = &mut self.firmware_generate;
+        generate.pcb_size = preset.pcb_size.to_string();
+        generate.dram_size = preset.dram_size_mb().map(dram_size_label).unwrap_or(preset.dram_size);
+        generate.package_code = preset.package_code.to_string();
+        generate.chip_count = preset.chip_count;
+        true
+    }
 
-        let pcb_size = match self.firmware_generate.pcb_size.parse::<u8>() {
-            Ok(size) if CONFIG.firmware.is_valid_pcb_size(size) => size,
-            Ok(_) => {
-                self.error_message = Some("无效的PCB尺寸代码".to_string());
-                return;
-            }
-            Err(_) => {
-                self.error_message = Some("无效的PCB尺寸".to_string());
-                return;
-            }
-        };
+    // 预设 + 表单中的日期，一键生成
+    pub fn generate_with_preset(&mut self) {
+        let name = self.presets.selected.clone();
+        if self.apply_preset(&name) {
+            self.generate_firmware();
+        }
+    }
 
-        let dram_size_mb = match self.firmware_generate.dram_size.parse::<i32>() {
-            Ok(size) if size == -1 || size > 0 => size,
-            Ok(_) => {
-                self.error_message = Some("DRAM大小必须为正数或-1(DRAMLess)".to_string());
-                return;
-            }
-            Err(_) => {
-                self.error_message = Some("无效的DRAM大小".to_string());
-                return;
-            }
-        };
+    // 当前表单保存为预设（同名则覆盖）
+    pub fn save_preset(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
 
-        let package_code = match self.firmware_generate.package_code.chars().next() {
-            Some(code) if CONFIG.firmware.is_valid_package(code) => code,
-            Some(_) => {
-                self.error_message = Some("无效的封装代码".to_string());
-                return;
-            }
-            None => {
-                self.error_message = Some("请输入封装代码".to_string());
+        let name = self.presets.new_name.trim().to_string();
+        if name.is_empty() {
+            self.error_message = Some("请输入预设名称".to_string());
+            return;
+        }
+        let generate = &self.firmware_generate;
+        let fields = generate.checked_pcb_size()
+            .and_then(|pcb| Ok((pcb, generate.checked_dram_size()?, generate.checked_package_code()?)));
+        let (pcb_size, dram_size_mb, package_code) = match fields {
+            Ok(fields) => fields,
+            Err(e) => {
+                self.error_message = Some(format!("无法保存预设: {}", e));
                 return;
             }
         };
-
-        let chip_count = match self.firmware_generate.chip_count.parse::<u8>() {
-            Ok(count) if CONFIG.firmware.is_valid_chip_count(count) => count,
-            Ok(_) => {
-                self.error_message = Some("颗粒数量必须在1-16之间".to_string());
-                return;
+        let profile = self.presets.new_profile.trim();
+        self.presets.presets.upsert(Preset {
+            name: name.clone(),
+            pcb_size,
+            dram_size: dram_size_label(dram_size_mb),
+            package_code: package_code.to_ascii_uppercase(),
+            chip_count: generate.chip_count,
+            profile: (!profile.is_empty()).then(|| profile.to_string()),
+        });
+        match self.presets.presets.save(Path::new(&self.presets.path)) {
+            Ok(()) => {
+                self.success_message = Some(format!("已保存预设 {}", name));
+                self.presets.selected = name;
+                self.presets.new_name.clear();
+                self.presets.new_profile.clear();
             }
-            Err(_) => {
-                self.error_message = Some("无效的颗粒数量".to_string());
-                return;
+            Err(e) => self.error_message = Some(format!("保存失败: {}", e)),
+        }
+    }
+
+    pub fn delete_preset(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+
+        let name = self.presets.selected.clone();
+        if !self.presets.presets.remove(&name) {
+            return;
+        }
+        match self.presets.presets.save(Path::new(&self.presets.path)) {
+            Ok(()) => {
+                self.success_message = Some(format!("已删除预设 {}", name));
+                self.presets.selected = self.presets.presets.presets.first().map(|p| p.name.clone()).unwrap_or_default();
             }
+            Err(e) => self.error_message = Some(format!("保存失败: {}", e)),
+        }
+    }
+
+    // 硬件预设：选择后一键生成，或把当前表单保存为预设
+    fn presets_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🧩 硬件预设")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let selected_text = match self.presets.presets.find(&self.presets.selected) {
+                        Some(preset) => format!("{} ({})", preset.name, preset.summary()),
+                        None => "（无预设）".to_string(),
+                    };
+                    egui::ComboBox::from_id_source("preset")
+                        .selected_text(selected_text)
+                        .width(260.0)
+                        .show_ui(ui, |ui| {
+                            for preset in &self.presets.presets.presets {
+                                let mut label = format!("{} ({})", preset.name, preset.summary());
+                                if !preset.problems().is_empty() {
+                                    label.push_str(" ⚠");
+                                }
+                                ui.selectable_value(&mut self.presets.selected, preset.name.clone(), label);
+                            }
+                        });
+
+                    let has_selection = self.presets.presets.find(&self.presets.selected).is_some();
+                    if ui.add_enabled(has_selection, egui::Button::new("⚡ 一键生成"))
+                        .on_hover_text("使用预设和上面的生产日期生成")
+                        .clicked()
+                    {
+                        self.generate_with_preset();
+                    }
+                    if ui.add_enabled(has_selection, egui::Button::new("填入表单")).clicked() {
+                        let name = self.presets.selected.clone();
+                        self.apply_preset(&name);
+                    }
+                    if ui.add_enabled(has_selection, egui::Button::new("删除")).clicked() {
+                        self.delete_preset();
+                    }
+                });
+
+                // 引用了已停用编码表项的预设
+                if let Some(preset) = self.presets.presets.find(&self.presets.selected) {
+                    let problems = preset.problems();
+                    if !problems.is_empty() {
+                        ui.colored_label(egui::Color32::from_rgb(230, 150, 0), format!("⚠ 预设已失效: {}", problems.join("；")));
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("保存当前表单为:");
+                    ui.add(egui::TextEdit::singleline(&mut self.presets.new_name)
+                        .desired_width(100.0)
+                        .hint_text("名称"));
+                    ui.add(egui::TextEdit::singleline(&mut self.presets.new_profile)
+                        .desired_width(100.0)
+                        .hint_text("用途（可选）"));
+                    if ui.button("💾 保存预设").clicked() {
+                        self.save_preset();
+                    }
+                });
+            });
+    }
+
+    // 记录到历史并保存（与最新一条相同时不重复记录）
+    fn record_history(&mut self, code: &str, action: HistoryAction) {
+        let entry = match HistoryEntry::new(code, action, self.clock.now()) {
+            Ok(entry) => entry,
+            Err(_) => return,
         };
+        if self.history.history.record(entry) {
+            self.save_history();
+        }
+    }
 
-        // 生成固件版本号
-        match FirmwareCodec::generate_firmware_code(
-            year, month, day, pcb_size, dram_size_mb, package_code, chip_count
-        ) {
-            Ok(code) => {
-                self.firmware_generate.generated_code = code;
-                self.success_message = Some("固件版本号生成成功！".to_string());
+    fn save_history(&mut self) {
+        if let Err(e) = self.history.history.save(Path::new(&self.history.path)) {
+            self.error_message = Some(format!("无法保存历史记录: {}", e));
+        }
+    }
+
+    // 将历史记录载入表单：生成的记录填入生成表单，解析的记录填入解析框
+    pub fn load_history_entry(&mut self, index: usize) {
+        self.error_message = None;
+        self.success_message = None;
+
+        let Some(entry) = self.history.history.entries.get(index) else {
+            return;
+        };
+        match entry.action {
+            HistoryAction::Parsed => {
+                self.firmware_parse.firmware_code = entry.code.clone();
+                self.firmware_parse.last_recorded = entry.code.clone();
             }
-            Err(e) => {
-                self.error_message = Some(format!("生成失败: {}", e));
+            HistoryAction::Generated => {
+                let Some((year, month, day, pcb_size, dram_size_mb, package_code, chip_count)) = entry.fields() else {
+                    self.error_message = Some(format!("{} 无法按当前配置解析", entry.code));
+                    return;
+                };
+                let generate = &mut self.firmware_generate;
+                generate.date = NaiveDate::from_ymd_opt(year, month, day).unwrap_or(generate.date);
+                generate.pcb_size = pcb_size.to_string();
+                generate.dram_size = dram_size_label(dram_size_mb);
+                generate.package_code = package_code.to_string();
+                generate.chip_count = chip_count;
+                generate.generated_code = entry.code.clone();
             }
         }
+        self.active_tab = Tab::Firmware;
+        self.success_message = Some(format!("已载入 {}", entry.code));
     }
 
-    // 解析固件版本号
-    pub fn parse_firmware(&mut self) {
-        // 清空之前的消息
+    // 导出当前搜索结果
+    pub fn export_history(&mut self) {
         self.error_message = None;
         self.success_message = None;
-        self.firmware_parse.parsed_result = None;
 
-        let code = self.firmware_parse.firmware_code.trim();
-        if code.is_empty() {
-            self.error_message = Some("请输入固件版本号".to_string());
+        let indices = self.history.history.search(&self.history.search);
+        let csv = self.history.history.to_csv(&indices);
+        match std::fs::write(&self.history.export_path, csv) {
+            Ok(()) => self.success_message = Some(format!("已导出 {} 条记录到 {}", indices.len(), self.history.export_path)),
+            Err(e) => self.error_message = Some(format!("导出失败: {}", e)),
+        }
+    }
+
+    // 历史记录侧栏
+    fn history_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("历史记录");
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(egui::TextEdit::singleline(&mut self.history.search)
+                .desired_width(f32::INFINITY)
+                .hint_text("版本号或摘要"));
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.history.export_path).desired_width(140.0));
+            if ui.button("导出CSV").clicked() {
+                self.export_history();
+            }
+        });
+        if ui.button("清除未固定").clicked() {
+            self.history.history.clear_unpinned();
+            self.save_history();
+        }
+        ui.separator();
+
+        let indices = self.history.history.search(&self.history.search);
+        if indices.is_empty() {
+            ui.weak("暂无记录");
             return;
         }
 
-        match FirmwareCodec::parse_firmware_code(code) {
-            Ok((year, month, day, pcb_size, dram_size_mb, package_code, chip_count)) => {
-                self.firmware_parse.parsed_result = Some(ParsedFirmware {
-                    year,
-                    month,
-                    day,
-                    pcb_size,
-                    dram_size_mb,
-                    package_code,
-                    chip_count,
+        let mut toggled = None;
+        let mut load = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for index in indices {
+                let entry = &self.history.history.entries[index];
+                ui.horizontal(|ui| {
+                    let star = if entry.pinned { "★" } else { "☆" };
+                    if ui.small_button(star).on_hover_text("固定").clicked() {
+                        toggled = Some(index);
+                    }
+                    ui.monospace(&entry.code);
+                    ui.weak(entry.action.label());
+                    if ui.small_button("载入").clicked() {
+                        load = Some(index);
+                    }
                 });
-                self.success_message = Some("固件版本号解析成功！".to_string());
-            }
-            Err(e) => {
-                self.error_message = Some(format!("解析失败: {}", e));
+                ui.small(format!(
+                    "{}  {}",
+                    entry.time.with_timezone(&Local).format("%m-%d %H:%M"),
+                    entry.summary
+                ));
+                ui.add_space(4.0);
             }
+        });
+
+        if let Some(index) = toggled {
+            self.history.history.toggle_pin(index);
+            self.save_history();
+        }
+        if let Some(index) = load {
+            self.load_history_entry(index);
         }
     }
 
@@ -242,6 +635,10 @@ impl SsdToolApp {
         // 标签页选择
         ui.horizontal(|ui| {
             ui.selectable_value(&mut self.active_tab, Tab::Firmware, "🔧 固件版本号");
+            ui.selectable_value(&mut self.active_tab, Tab::Compare, "⚖ 版本号比较");
+            ui.selectable_value(&mut self.active_tab, Tab::Extract, "📋 文本提取");
+            ui.selectable_value(&mut self.active_tab, Tab::Catalog, "📚 发布目录");
+            ui.selectable_value(&mut self.active_tab, Tab::Config, "⚙ 配置");
         });
 
         ui.separator();
@@ -259,6 +656,10 @@ impl SsdToolApp {
         // 根据当前标签页显示内容
         match self.active_tab {
             Tab::Firmware => self.firmware_ui(ui),
+            Tab::Compare => self.compare_ui(ui),
+            Tab::Extract => self.extract_ui(ui),
+            Tab::Catalog => self.catalog_ui(ui),
+            Tab::Config => self.config_ui(ui),
         }
     }
 
@@ -284,6 +685,17 @@ impl SsdToolApp {
 
     // 固件版本号生成UI
     fn firmware_generate_ui(&mut self, ui: &mut egui::Ui) {
+        let limits = match CONFIG.firmware.limits() {
+            Ok(limits) => limits,
+            Err(e) => {
+                self.show_error(ui, &format!("配置错误: {}", e));
+                return;
+            }
+        };
+
+        self.presets_ui(ui);
+        ui.add_space(5.0);
+
         egui::Grid::new("firmware_generate_grid")
             .num_columns(2)
             .spacing([20.0, 10.0])
@@ -291,15 +703,20 @@ impl SsdToolApp {
                 // 生产日期
                 ui.label("📅 生产日期:");
                 ui.horizontal(|ui| {
-                    ui.label("年");
-                    ui.add(egui::TextEdit::singleline(&mut self.firmware_generate.year)
-                        .desired_width(60.0));
-                    ui.label("月");
-                    ui.add(egui::TextEdit::singleline(&mut self.firmware_generate.month)
-                        .desired_width(40.0));
-                    ui.label("日");
-                    ui.add(egui::TextEdit::singleline(&mut self.firmware_generate.day)
-                        .desired_width(40.0));
+                    let response = ui.add(egui_extras::DatePickerButton::new(&mut self.firmware_generate.date)
+                        .id_source("generate_date")
+                        .calendar_week(true));
+                    // 日历选择限制在可编码范围内
+                    if response.changed() {
+                        self.firmware_generate.date = self.firmware_generate.date.clamp(limits.first_date, limits.last_date);
+                    }
+                    if ui.button("今天").on_hover_text("当前生产日期（工厂时区）").clicked() {
+                        self.set_today();
+                    }
+                    match self.firmware_generate.checked_date(&limits) {
+                        Ok(_) => ui.weak(format!("({})", limits.date_range_desc())),
+                        Err(e) => mark_invalid(ui, &response, &e),
+                    };
                 });
                 ui.end_row();
 
@@ -313,31 +730,45 @@ impl SsdToolApp {
                                                 )
                     );
 
-                    egui::ComboBox::from_id_source("pcb_size")
+                    let response = egui::ComboBox::from_id_source("pcb_size")
                         .selected_text(pcb_size_text)
                         .show_ui(ui, |ui| {
-                            for (code, desc) in &CONFIG.firmware.pcb_sizes {
+                            for &code in limits.pcb_sizes.iter().filter(|&&size| !CONFIG.firmware.is_retired_pcb_size(size)) {
                                 let code_str = code.to_string();
                                 if ui.selectable_label(
-                                    &self.firmware_generate.pcb_size == &code_str,
-                                    format!("{} - {}", code, desc)
+                                    self.firmware_generate.pcb_size == code_str,
+                                    format!("{} - {}", code, CONFIG.firmware.get_pcb_size_name(code))
                                 ).clicked() {
                                     self.firmware_generate.pcb_size = code_str;
                                 }
                             }
-                        });
+                        })
+                        .response;
+                    if let Err(e) = self.firmware_generate.checked_pcb_size() {
+                        mark_invalid(ui, &response, &e);
+                    }
                 });
                 ui.end_row();
 
                 // DRAM大小
                 ui.label("💾 DRAM大小:");
                 ui.horizontal(|ui| {
-                    if ui.button("DRAMLess").clicked() {
-                        self.firmware_generate.dram_size = "-1".to_string();
+                    egui::ComboBox::from_id_source("dram_size")
+                        .selected_text(self.firmware_generate.dram_size.clone())
+                        .show_ui(ui, |ui| {
+                            for size in CONFIG.firmware.supported_dram_sizes() {
+                                let label = dram_size_label(size);
+                                if ui.selectable_label(self.firmware_generate.dram_size == label, label.as_str()).clicked() {
+                                    self.firmware_generate.dram_size = label;
+                                }
+                            }
+                        });
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.firmware_generate.dram_size)
+                        .desired_width(80.0)
+                        .hint_text("如 1GB"));
+                    if let Err(e) = self.firmware_generate.checked_dram_size() {
+                        mark_invalid(ui, &response, &e);
                     }
-                    ui.add(egui::TextEdit::singleline(&mut self.firmware_generate.dram_size)
-                        .desired_width(80.0));
-                    ui.label("MB");
                 });
                 ui.end_row();
 
@@ -350,30 +781,54 @@ impl SsdToolApp {
                                            )
                 );
 
-                egui::ComboBox::from_id_source("package")
-                    .selected_text(package_text)
-                    .show_ui(ui, |ui| {
-                        for (code, desc) in &CONFIG.firmware.packages {
-                            let code_str = code.to_string();
-                            if ui.selectable_label(
-                                &self.firmware_generate.package_code == &code_str,
-                                format!("{} - {}", code, desc)
-                            ).clicked() {
-                                self.firmware_generate.package_code = code_str;
+                ui.horizontal(|ui| {
+                    let response = egui::ComboBox::from_id_source("package")
+                        .selected_text(package_text)
+                        .show_ui(ui, |ui| {
+                            for &code in limits.packages.iter().filter(|&&code| !CONFIG.firmware.is_retired_package(code)) {
+                                let code_str = code.to_string();
+                                if ui.selectable_label(
+                                    self.firmware_generate.package_code == code_str,
+                                    format!("{} - {}", code, CONFIG.firmware.get_package_name(code))
+                                ).clicked() {
+                                    self.firmware_generate.package_code = code_str;
+                                }
                             }
-                        }
-                    });
+                        })
+                        .response;
+                    if let Err(e) = self.firmware_generate.checked_package_code() {
+                        mark_invalid(ui, &response, &e);
+                    }
+                });
                 ui.end_row();
 
                 // 颗粒数量
                 ui.label("🔢 颗粒数量:");
-                ui.add(egui::TextEdit::singleline(&mut self.firmware_generate.chip_count)
-                    .desired_width(60.0));
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::DragValue::new(&mut self.firmware_generate.chip_count)
+                        .clamp_range(limits.chip_range())
+                        .speed(0.1));
+                    match self.firmware_generate.checked_chip_count(&limits) {
+                        Ok(_) => ui.weak(format!("({}-{})", limits.chip_min, limits.chip_max)),
+                        Err(e) => mark_invalid(ui, &response, &e),
+                    };
+                });
                 ui.end_row();
             });
 
         ui.add_space(10.0);
 
+        // 实时预览（所有字段有效时显示）
+        ui.horizontal(|ui| {
+            ui.label("预览:");
+            match self.firmware_generate.preview(&limits) {
+                Ok(code) => ui.monospace(code),
+                Err(_) => ui.weak("（请修正标红的字段）"),
+            };
+        });
+
+        ui.add_space(10.0);
+
         // 生成按钮
         if ui.button("🚀 生成固件版本号").clicked() {
             self.generate_firmware();
@@ -404,60 +859,902 @@ impl SsdToolApp {
         ui.horizontal(|ui| {
             ui.label("固件版本号:");
             ui.add(egui::TextEdit::singleline(&mut self.firmware_parse.firmware_code)
-                .desired_width(150.0));
+                .desired_width(150.0)
+                .hint_text("输入即解析"));
+        });
+
+        let code = self.firmware_parse.firmware_code.trim();
+        if code.is_empty() {
+            return;
+        }
+        let limits = match CONFIG.firmware.limits() {
+            Ok(limits) => limits,
+            Err(e) => {
+                self.show_error(ui, &format!("配置错误: {}", e));
+                return;
+            }
+        };
+
+        // 按字段着色显示，下方为解码结果（悬停查看该位置的全部代码）
+        let segments = code_segments::decode_segments(code);
+        ui.add_space(10.0);
+        egui::Frame::group(ui.style())
+            .inner_margin(egui::Margin::symmetric(10.0, 5.0))
+            .show(ui, |ui| {
+                ui.horizontal_top(|ui| {
+                    for (segment, color) in segments.iter().zip(SEGMENT_COLORS) {
+                        let width = segment.field.range().len();
+                        ui.vertical(|ui| {
+                            ui.label(egui::RichText::new(format!("{:·<width$}", segment.text, width = width))
+                                .monospace()
+                                .size(22.0)
+                                .strong()
+                                .color(color));
+                            ui.small(segment.field.label());
+                            match &segment.state {
+                                SegmentState::Incomplete => ui.weak("…"),
+                                SegmentState::Valid(meaning) => ui.label(meaning),
+                                SegmentState::Invalid(error) => ui.colored_label(egui::Color32::RED, error),
+                            };
+                        })
+                        .response
+                        .on_hover_ui(|ui| code_table_ui(ui, segment.field, &limits));
+                        ui.add_space(8.0);
+                    }
+                });
+            });
+
+        // 所有字段完整且有效时再整体校验一次
+        let length = code.chars().count();
+        if length > FIRMWARE_CODE_LEN {
+            self.show_error(ui, &format!("版本号过长 (应为 {} 位，实际 {} 位)", FIRMWARE_CODE_LEN, length));
+        } else if segments.iter().all(|segment| matches!(segment.state, SegmentState::Valid(_))) {
+            match FirmwareCodec::parse_firmware_code(code) {
+                Ok(_) => {
+                    self.show_success(ui, "固件版本号有效");
+                    let code = code.to_ascii_uppercase();
+                    if code != self.firmware_parse.last_recorded {
+                        self.record_history(&code, HistoryAction::Parsed);
+                        self.firmware_parse.last_recorded = code;
+                    }
+                }
+                Err(e) => self.show_error(ui, &format!("解析失败: {}", e)),
+            }
+        }
+    }
+
+    // 拖入文件：读取文本并提取其中的版本号
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        if dropped.is_empty() {
+            return;
+        }
+
+        self.error_message = None;
+        self.success_message = None;
+        let mut texts = Vec::new();
+        for file in dropped {
+            let data = match (&file.path, &file.bytes) {
+                (_, Some(bytes)) => bytes.to_vec(),
+                (Some(path), None) => match std::fs::read(path) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        self.error_message = Some(format!("无法读取 {}: {}", path.display(), e));
+                        continue;
+                    }
+                },
+                (None, None) => continue,
+            };
+            texts.push(String::from_utf8_lossy(&data).into_owned());
+        }
+        if !texts.is_empty() {
+            self.extract.text = texts.join("\n");
+            self.refresh_extract();
+            self.active_tab = Tab::Extract;
+        }
+    }
 
-            if ui.button("🔍 解析").clicked() {
-                self.parse_firmware();
+    // 重新提取并排序
+    fn refresh_extract(&mut self) {
+        self.extract.rows = code_extract::extract_codes(&self.extract.text);
+        self.sort_extract();
+    }
+
+    fn sort_extract(&mut self) {
+        let (column, ascending) = (self.extract.sort, self.extract.ascending);
+        self.extract.rows.sort_by(|a, b| {
+            let ordering = column.compare(a, b);
+            if ascending { ordering } else { ordering.reverse() }
+        });
+    }
+
+    // 当前筛选条件下显示的行
+    fn visible_extract_rows(&self) -> Vec<&ExtractedCode> {
+        self.extract.rows.iter()
+            .filter(|row| !self.extract.only_errors || row.fields.is_err())
+            .filter(|row| row.contains(&self.extract.filter))
+            .collect()
+    }
+
+    // 文本提取UI：粘贴文本或拖入文件，列出其中所有版本号的解码结果
+    fn extract_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("文本提取");
+        ui.label("粘贴邮件、测试报告等文本，或把文件拖入窗口，提取其中所有的固件版本号。");
+        let response = ui.add(egui::TextEdit::multiline(&mut self.extract.text)
+            .desired_rows(6)
+            .desired_width(f32::INFINITY)
+            .hint_text("在此粘贴文本"));
+        if response.changed() {
+            self.refresh_extract();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(egui::TextEdit::singleline(&mut self.extract.filter)
+                .desired_width(150.0)
+                .hint_text("筛选"));
+            ui.checkbox(&mut self.extract.only_errors, "只看错误");
+
+            if ui.button("📋 复制CSV").clicked() {
+                ui.ctx().copy_text(code_extract::to_csv(self.visible_extract_rows()));
+            }
+            ui.add(egui::TextEdit::singleline(&mut self.extract.export_path).desired_width(140.0));
+            if ui.button("导出CSV").clicked() {
+                self.error_message = None;
+                self.success_message = None;
+                let csv = code_extract::to_csv(self.visible_extract_rows());
+                match std::fs::write(&self.extract.export_path, csv) {
+                    Ok(()) => self.success_message = Some(format!("已导出到 {}", self.extract.export_path)),
+                    Err(e) => self.error_message = Some(format!("导出失败: {}", e)),
+                }
             }
         });
 
-        // 显示解析结果
-        if let Some(parsed) = &self.firmware_parse.parsed_result {
-            ui.add_space(10.0);
-            egui::Frame::group(ui.style())
-                .inner_margin(egui::Margin::symmetric(10.0, 5.0))
+        let errors = self.extract.rows.iter().filter(|row| row.fields.is_err()).count();
+        ui.label(format!("共 {} 个候选版本号，其中 {} 个无法解析", self.extract.rows.len(), errors));
+        ui.add_space(5.0);
+
+        let mut clicked_column = None;
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new("extract_grid")
+                .num_columns(SortColumn::ALL.len())
+                .spacing([15.0, 4.0])
+                .striped(true)
                 .show(ui, |ui| {
-                    egui::Grid::new("parse_result_grid")
-                        .num_columns(2)
-                        .spacing([10.0, 5.0])
-                        .show(ui, |ui| {
-                            ui.label("生产日期:");
-                            ui.label(format!("{}-{:02}-{:02}",
-                                             parsed.year, parsed.month, parsed.day));
-                            ui.end_row();
-
-                            ui.label("PCB尺寸:");
-                            ui.label(format!("{} ({})",
-                                             parsed.pcb_size,
-                                             CONFIG.firmware.get_pcb_size_name(parsed.pcb_size)));
-                            ui.end_row();
-
-                            ui.label("DRAM大小:");
-                            if parsed.dram_size_mb == -1 {
-                                ui.label("DRAMLess");
-                            } else {
-                                ui.label(format!("{}MB", parsed.dram_size_mb));
-                            }
-                            ui.end_row();
+                    // 点击表头排序，再次点击反向
+                    for column in SortColumn::ALL {
+                        let arrow = match (self.extract.sort == column, self.extract.ascending) {
+                            (true, true) => " ⬆",
+                            (true, false) => " ⬇",
+                            (false, _) => "",
+                        };
+                        if ui.button(format!("{}{}", column.label(), arrow)).clicked() {
+                            clicked_column = Some(column);
+                        }
+                    }
+                    ui.end_row();
+
+                    for row in self.visible_extract_rows() {
+                        ui.label(row.line.to_string());
+                        if row.fields.is_ok() {
+                            ui.monospace(&row.code);
+                        } else {
+                            ui.label(egui::RichText::new(&row.code).monospace().color(egui::Color32::RED));
+                        }
+                        ui.label(row.date());
+                        ui.label(row.pcb());
+                        ui.label(row.dram());
+                        ui.label(row.package());
+                        ui.label(row.chips());
+                        ui.colored_label(egui::Color32::RED, row.error());
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some(column) = clicked_column {
+            if self.extract.sort == column {
+                self.extract.ascending = !self.extract.ascending;
+            } else {
+                self.extract.sort = column;
+                self.extract.ascending = true;
+            }
+            self.sort_extract();
+        }
+    }
+
+    // 版本号比较UI：以第一个版本号为基准逐字段比较
+    fn compare_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("版本号比较");
+        ui.label("每行输入一个固件版本号（第一个为基准，如预期版本），不同的字段会标出。");
+        ui.add(egui::TextEdit::multiline(&mut self.compare.input)
+            .desired_rows(4)
+            .desired_width(200.0)
+            .font(egui::TextStyle::Monospace)
+            .hint_text("S5EJ13B4\nS5F013B4"));
+        ui.add_space(10.0);
+
+        let codes: Vec<&str> = self.compare.input
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|code| !code.is_empty())
+            .collect();
+        if codes.len() < 2 {
+            ui.weak("至少需要两个固件版本号");
+            return;
+        }
+        let comparison = match Comparison::new(&codes) {
+            Ok(comparison) => comparison,
+            Err(e) => {
+                self.show_error(ui, &e.to_string());
+                return;
+            }
+        };
+
+        let highlight = egui::Color32::from_rgb(230, 120, 0);
+        egui::Grid::new("compare_grid")
+            .num_columns(comparison.codes.len() + 1)
+            .spacing([20.0, 6.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("字段");
+                for c in &comparison.codes {
+                    ui.strong(egui::RichText::new(&c.code).monospace());
+                }
+                ui.end_row();
+
+                for row in comparison.rows() {
+                    if row.differs {
+                        ui.colored_label(highlight, format!("≠ {}", row.field.label()));
+                    } else {
+                        ui.label(row.field.label());
+                    }
+                    for (i, value) in row.values.iter().enumerate() {
+                        // 与基准不同的值高亮
+                        if i > 0 && *value != row.values[0] {
+                            ui.label(egui::RichText::new(value).color(highlight).strong());
+                        } else {
+                            ui.label(value);
+                        }
+                    }
+                    ui.end_row();
+                }
+
+                ui.label("日期差");
+                for c in &comparison.codes {
+                    ui.label(format!("{:+} 天", c.days_from_first));
+                }
+                ui.end_row();
+            });
+
+        ui.add_space(10.0);
+        let verdict = comparison.verdict();
+        let color = match verdict {
+            Verdict::Identical | Verdict::DateOnly => egui::Color32::GREEN,
+            Verdict::Incompatible(_) => egui::Color32::RED,
+        };
+        ui.colored_label(color, format!("结论: {}", verdict.label()));
+        if let Verdict::Incompatible(differences) = &verdict {
+            for diff in differences {
+                ui.label(format!("  {}", diff));
+            }
+        }
+    }
+
+    // 加载发布目录
+    pub fn load_catalog(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+
+        match Catalog::load(Path::new(&self.catalog.path)) {
+            Ok(catalog) => {
+                self.success_message = Some(format!("已加载 {} 条记录", catalog.records.len()));
+                self.catalog.catalog = Some(catalog);
+                self.catalog.load_failed = false;
+                self.catalog.dirty = false;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("加载失败: {}", e));
+                self.catalog.load_failed = true;
+            }
+        }
+    }
+
+    // 保存发布目录
+    pub fn save_catalog(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+
+        let Some(catalog) = &self.catalog.catalog else {
+            return;
+        };
+        match catalog.save(Path::new(&self.catalog.path)) {
+            Ok(()) => {
+                self.catalog.dirty = false;
+                self.success_message = Some("发布目录已保存".to_string());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("保存失败: {}", e));
+            }
+        }
+    }
+
+    // 添加目录记录
+    pub fn add_catalog_record(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+
+        let Some(catalog) = &mut self.catalog.catalog else {
+            return;
+        };
+        let result = CatalogRecord::new(&self.catalog.new_code, self.catalog.new_status)
+            .and_then(|mut record| {
+                record.notes = self.catalog.new_notes.trim().to_string();
+                catalog.add(record)
+            });
 
-                            ui.label("封装类型:");
-                            ui.label(format!("{} ({})",
-                                             parsed.package_code,
-                                             CONFIG.firmware.get_package_name(parsed.package_code)));
-                            ui.end_row();
+        match result {
+            Ok(()) => {
+                self.success_message = Some(format!("已添加 {}", self.catalog.new_code.trim().to_ascii_uppercase()));
+                self.catalog.new_code.clear();
+                self.catalog.new_notes.clear();
+                self.catalog.dirty = true;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("添加失败: {}", e));
+            }
+        }
+    }
+
+    // 根据界面条件构建查询，无效的条件返回错误说明
+    fn catalog_query(&self) -> Result<CatalogQuery, String> {
+        let state = &self.catalog;
+        let mut query = CatalogQuery {
+            pcb_size: state.pcb_size,
+            dram_size_mb: state.dram_size_mb,
+            package_code: state.package_code,
+            status: state.status,
+            latest: state.latest,
+            ..Default::default()
+        };
+
+        if !state.chip_count.trim().is_empty() {
+            query.chip_count = Some(state.chip_count.trim().parse().map_err(|_| "无效的颗粒数量".to_string())?);
+        }
+        if !state.from.trim().is_empty() {
+            query.from = Some(factory_clock::resolve_date(&state.from, self.clock.as_ref()).map_err(|e| e.to_string())?);
+        }
+        if !state.to.trim().is_empty() {
+            query.to = Some(factory_clock::resolve_date(&state.to, self.clock.as_ref()).map_err(|e| e.to_string())?);
+        }
+        Ok(query)
+    }
+
+    // 发布目录UI
+    fn catalog_ui(&mut self, ui: &mut egui::Ui) {
+        if self.catalog.catalog.is_none() && !self.catalog.load_failed {
+            self.load_catalog();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("目录文件:");
+            ui.add(egui::TextEdit::singleline(&mut self.catalog.path).desired_width(250.0));
+            if ui.button("📂 加载").clicked() {
+                self.load_catalog();
+            }
+            if ui.add_enabled(self.catalog.dirty, egui::Button::new("💾 保存")).clicked() {
+                self.save_catalog();
+            }
+            if self.catalog.dirty {
+                ui.colored_label(egui::Color32::YELLOW, "有未保存的修改");
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.heading("查询条件");
+        self.catalog_filter_ui(ui);
+
+        ui.add_space(10.0);
+        ui.heading("查询结果");
+        match self.catalog_query() {
+            Ok(query) => self.catalog_results_ui(ui, &query),
+            Err(e) => self.show_error(ui, &e),
+        }
+
+        ui.add_space(10.0);
+        ui.heading("添加记录");
+        ui.horizontal(|ui| {
+            ui.label("固件版本号:");
+            ui.add(egui::TextEdit::singleline(&mut self.catalog.new_code).desired_width(100.0));
+            egui::ComboBox::from_id_source("catalog_new_status")
+                .selected_text(self.catalog.new_status.label())
+                .show_ui(ui, |ui| {
+                    for status in ReleaseStatus::ALL {
+                        ui.selectable_value(&mut self.catalog.new_status, status, status.label());
+                    }
+                });
+            ui.label("说明:");
+            ui.add(egui::TextEdit::singleline(&mut self.catalog.new_notes).desired_width(200.0));
+            if ui.button("➕ 添加").clicked() {
+                self.add_catalog_record();
+            }
+        });
+    }
+
+    // 查询条件UI
+    fn catalog_filter_ui(&mut self, ui: &mut egui::Ui) {
+        let state = &mut self.catalog;
+        egui::Grid::new("catalog_filter_grid")
+            .num_columns(4)
+            .spacing([10.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("PCB尺寸:");
+                egui::ComboBox::from_id_source("catalog_pcb")
+                    .selected_text(state.pcb_size
+                        .map(|s| format!("{} - {}", s, CONFIG.firmware.get_pcb_size_name(s)))
+                        .unwrap_or_else(|| "全部".to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.pcb_size, None, "全部");
+                        for (&code, desc) in &CONFIG.firmware.pcb_sizes {
+                            ui.selectable_value(&mut state.pcb_size, Some(code), format!("{} - {}", code, desc));
+                        }
+                    });
+
+                ui.label("DRAM大小:");
+                let mut dram_sizes: Vec<(&char, &i32)> = CONFIG.firmware.dram_sizes.iter().collect();
+                dram_sizes.sort_by_key(|(_, &size)| size);
+                egui::ComboBox::from_id_source("catalog_dram")
+                    .selected_text(state.dram_size_mb
+                        .and_then(|size| FirmwareCodec::dram_size_to_code(size).ok())
+                        .map(|code| CONFIG.firmware.get_dram_size_desc(code))
+                        .unwrap_or_else(|| "全部".to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.dram_size_mb, None, "全部");
+                        for (&code, &size) in dram_sizes {
+                            ui.selectable_value(&mut state.dram_size_mb, Some(size), CONFIG.firmware.get_dram_size_desc(code));
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("封装类型:");
+                egui::ComboBox::from_id_source("catalog_package")
+                    .selected_text(state.package_code
+                        .map(|c| format!("{} - {}", c, CONFIG.firmware.get_package_name(c)))
+                        .unwrap_or_else(|| "全部".to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.package_code, None, "全部");
+                        for (&code, desc) in &CONFIG.firmware.packages {
+                            ui.selectable_value(&mut state.package_code, Some(code), format!("{} - {}", code, desc));
+                        }
+                    });
 
-                            ui.label("颗粒数量:");
-                            ui.label(parsed.chip_count.to_string());
-                            ui.end_row();
+                ui.label("颗粒数量:");
+                ui.add(egui::TextEdit::singleline(&mut state.chip_count).desired_width(60.0).hint_text("全部"));
+                ui.end_row();
+
+                ui.label("发布状态:");
+                egui::ComboBox::from_id_source("catalog_status")
+                    .selected_text(state.status.map(|s| s.label()).unwrap_or("全部"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.status, None, "全部");
+                        for status in ReleaseStatus::ALL {
+                            ui.selectable_value(&mut state.status, Some(status), status.label());
+                        }
+                    });
+
+                ui.label("日期范围:");
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut state.from).desired_width(90.0).hint_text("YYYY-MM-DD"));
+                    ui.label("至");
+                    ui.add(egui::TextEdit::singleline(&mut state.to).desired_width(90.0).hint_text("YYYY-MM-DD"));
+                });
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut state.latest, "只显示最新一条");
+                ui.end_row();
+            });
+    }
+
+    // 查询结果UI
+    fn catalog_results_ui(&mut self, ui: &mut egui::Ui, query: &CatalogQuery) {
+        let Some(catalog) = &mut self.catalog.catalog else {
+            return;
+        };
+        let codes: Vec<String> = catalog.query(query).iter().map(|r| r.code.clone()).collect();
+        if codes.is_empty() {
+            ui.label("没有匹配的记录");
+            return;
+        }
+
+        let mut changed = false;
+        egui::Grid::new("catalog_result_grid")
+            .num_columns(6)
+            .striped(true)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for header in ["固件版本号", "日期", "硬件", "状态", "文件", "说明"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                for code in &codes {
+                    let Some(record) = catalog.records.iter_mut().find(|r| &r.code == code) else {
+                        continue;
+                    };
+                    let Some((year, month, day, pcb_size, dram_size_mb, package_code, chip_count)) = record.fields else {
+                        continue;
+                    };
+
+                    ui.monospace(&record.code);
+                    ui.label(format!("{}-{:02}-{:02}", year, month, day));
+                    let dram = FirmwareCodec::dram_size_to_code(dram_size_mb)
+                        .map(|c| CONFIG.firmware.get_dram_size_desc(c))
+                        .unwrap_or_default();
+                    ui.label(format!(
+                        "PCB{} / {} / {} / {}颗",
+                        pcb_size, dram, CONFIG.firmware.get_package_name(package_code), chip_count
+                    ));
+                    egui::ComboBox::from_id_source(("catalog_status_row", code))
+                        .selected_text(record.status.label())
+                        .show_ui(ui, |ui| {
+                            for status in ReleaseStatus::ALL {
+                                changed |= ui.selectable_value(&mut record.status, status, status.label()).changed();
+                            }
                         });
+                    ui.label(record.file.as_deref().unwrap_or("-"));
+                    ui.label(&record.notes);
+                    ui.end_row();
+                }
+            });
+
+        if changed {
+            self.catalog.dirty = true;
+        }
+    }
+
+    // 加载配置文件到编辑器
+    pub fn load_config_editor(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+
+        match ConfigEditor::load(Path::new(&self.config_editor.path)) {
+            Ok(editor) => {
+                self.success_message = Some(if editor.is_new {
+                    format!("{} 不存在，已载入当前配置，保存时新建该文件", self.config_editor.path)
+                } else {
+                    format!("已加载 {}", self.config_editor.path)
+                });
+                self.config_editor.editor = Some(editor);
+                self.config_editor.load_failed = false;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("加载失败: {}", e));
+                self.config_editor.load_failed = true;
+            }
+        }
+    }
+
+    // 验证并保存配置（先备份原文件）
+    pub fn save_config_editor(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+
+        let Some(editor) = &mut self.config_editor.editor else {
+            return;
+        };
+        match editor.save() {
+            Ok(backup) => {
+                let backup = backup.map(|path| format!("，原文件已备份为 {}", path.display())).unwrap_or_default();
+                let active = config::config_path().is_some_and(|path| path == editor.path);
+                self.success_message = Some(if active {
+                    format!("配置已保存{}，重启程序后生效", backup)
+                } else {
+                    format!("配置已保存{}，使用 --config {} 启动后生效", backup, editor.path.display())
                 });
+            }
+            Err(e) => {
+                self.error_message = Some(format!("保存失败: {}", e));
+            }
+        }
+    }
+
+    // 配置UI
+    fn config_ui(&mut self, ui: &mut egui::Ui) {
+        if self.config_editor.editor.is_none() && !self.config_editor.load_failed {
+            self.load_config_editor();
+        }
+
+        let modified = self.config_editor.editor.as_ref().is_some_and(|editor| editor.is_modified());
+        let validation = self.config_editor.editor.as_ref().map(|editor| editor.config.validate());
+        ui.horizontal(|ui| {
+            ui.label("配置文件:");
+            ui.add(egui::TextEdit::singleline(&mut self.config_editor.path).desired_width(250.0));
+            if ui.button("📂 加载").clicked() {
+                self.load_config_editor();
+            }
+            let can_save = modified && matches!(validation, Some(Ok(())));
+            if ui.add_enabled(can_save, egui::Button::new("💾 保存")).clicked() {
+                self.save_config_editor();
+            }
+            ui.toggle_value(&mut self.config_editor.show_diff, "🔍 差异");
+            if modified {
+                ui.colored_label(egui::Color32::YELLOW, "有未保存的修改");
+            }
+        });
+        match &validation {
+            Some(Ok(())) => ui.colored_label(egui::Color32::GREEN, "✔ 配置验证通过"),
+            Some(Err(e)) => ui.colored_label(egui::Color32::RED, format!("✖ {}", e)),
+            None => return,
+        };
+        ui.weak("保存时只改写修改过的条目（保留注释和格式），并先备份原文件；以 --config <文件> 启动程序后生效。");
+
+        ui.add_space(10.0);
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if self.config_editor.show_diff {
+                self.config_diff_ui(ui);
+                ui.add_space(10.0);
+            }
+            self.config_tables_ui(ui);
+        });
+    }
+
+    // 与磁盘文件的差异
+    fn config_diff_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(editor) = &self.config_editor.editor else {
+            return;
+        };
+        ui.heading("与磁盘文件的差异");
+        let lines = match editor.diff() {
+            Ok(lines) => lines,
+            Err(e) => {
+                self.show_error(ui, &e.to_string());
+                return;
+            }
+        };
+        if lines.iter().all(|line| matches!(line, DiffLine::Same(_))) {
+            ui.label("没有差异");
+            return;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_source("config_diff_scroll")
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for line in &lines {
+                        let (text, color) = match line {
+                            DiffLine::Same(l) => (format!("  {}", l), ui.visuals().weak_text_color()),
+                            DiffLine::Added(l) => (format!("+ {}", l), egui::Color32::GREEN),
+                            DiffLine::Removed(l) => (format!("- {}", l), egui::Color32::RED),
+                        };
+                        ui.label(egui::RichText::new(text).monospace().color(color));
+                    }
+                });
+        });
+    }
+
+    // 各编码表（按代码排序，可编辑、添加、停用或恢复）
+    fn config_tables_ui(&mut self, ui: &mut egui::Ui) {
+        let state = &mut self.config_editor;
+        let Some(editor) = &mut state.editor else {
+            return;
+        };
+        let mut error = None;
+
+        ui.heading("基本设置");
+        let config = &mut editor.config;
+        egui::Grid::new("config_basic_grid")
+            .num_columns(2)
+            .spacing([10.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("基准日期:");
+                ui.label(format!("{}-{:02}-{:02}", config.base_date.year, config.base_date.month, config.base_date.day));
+                ui.end_row();
+                ui.label("纪元:");
+                ui.label(format!("{} (共 {} 个)", config.era, config.era_count()));
+                ui.end_row();
+                ui.label("日期编码:");
+                ui.label(format!("{} ({})", config.date_encoding.name(), config.date_encoding.label()));
+                ui.end_row();
+                ui.label("编码字符集:");
+                ui.monospace(&config.base32_chars);
+                ui.end_row();
+                ui.label("颗粒数量范围:");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut config.chip_count.min).clamp_range(1..=u8::MAX));
+                    ui.label("-");
+                    ui.add(egui::DragValue::new(&mut config.chip_count.max).clamp_range(1..=u8::MAX));
+                });
+                ui.end_row();
+                ui.label("颗粒数量代码:");
+                ui.add(egui::TextEdit::singleline(&mut config.chip_count.codes)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(250.0));
+                ui.end_row();
+            });
+
+        ui.add_space(10.0);
+        ui.heading("PCB尺寸");
+        let mut retired_pcb = None;
+        egui::Grid::new("config_pcb_grid")
+            .num_columns(4)
+            .striped(true)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for header in ["尺寸", "代码", "名称", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                let codes: Vec<Option<char>> = config.pcb_sizes.keys()
+                    .map(|&size| config.pcb_size_code(size).ok())
+                    .collect();
+                for ((&size, name), code) in config.pcb_sizes.iter_mut().zip(codes) {
+                    ui.label(size.to_string());
+                    ui.monospace(code.map(String::from).unwrap_or_else(|| "?".to_string()));
+                    ui.add(egui::TextEdit::singleline(name).desired_width(200.0));
+                    if retire_button(ui, config.retired.pcb_sizes.contains(&size)) {
+                        retired_pcb = Some(size);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(size) = retired_pcb {
+            toggle_retired(&mut config.retired.pcb_sizes, size);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut state.new_pcb_size).desired_width(40.0).hint_text("尺寸"));
+            ui.add(egui::TextEdit::singleline(&mut state.new_pcb_name).desired_width(200.0).hint_text("名称"));
+            if ui.button("➕ 添加/修改").clicked() {
+                match editor.set_pcb_size(&state.new_pcb_size, &state.new_pcb_name) {
+                    Ok(_) => {
+                        state.new_pcb_size.clear();
+                        state.new_pcb_name.clear();
+                    }
+                    Err(e) => error = Some(e.to_string()),
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.heading("DRAM大小");
+        let mut retired_dram = None;
+        egui::Grid::new("config_dram_grid")
+            .num_columns(4)
+            .striped(true)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for header in ["代码", "大小", "MB", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for (&code, &size) in &editor.config.dram_sizes {
+                    ui.monospace(code.to_string());
+                    ui.label(dram_size_label(size));
+                    ui.label(size.to_string());
+                    if retire_button(ui, editor.config.retired.dram_sizes.contains(&code)) {
+                        retired_dram = Some(code);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(code) = retired_dram {
+            toggle_retired(&mut editor.config.retired.dram_sizes, code);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut state.new_dram_code).desired_width(40.0).hint_text("代码"));
+            ui.add(egui::TextEdit::singleline(&mut state.new_dram_size).desired_width(100.0).hint_text("1GB / DRAMLess"));
+            if ui.button("➕ 添加/修改").clicked() {
+                match editor.set_dram_size(&state.new_dram_code, &state.new_dram_size) {
+                    Ok(_) => {
+                        state.new_dram_code.clear();
+                        state.new_dram_size.clear();
+                    }
+                    Err(e) => error = Some(e.to_string()),
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.heading("封装");
+        let mut retired_package = None;
+        egui::Grid::new("config_package_grid")
+            .num_columns(3)
+            .striped(true)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for header in ["代码", "名称", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                let retired = &editor.config.retired.packages;
+                for (&code, name) in editor.config.packages.iter_mut() {
+                    ui.monospace(code.to_string());
+                    ui.add(egui::TextEdit::singleline(name).desired_width(200.0));
+                    if retire_button(ui, retired.contains(&code)) {
+                        retired_package = Some(code);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(code) = retired_package {
+            toggle_retired(&mut editor.config.retired.packages, code);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut state.new_package_code).desired_width(40.0).hint_text("代码"));
+            ui.add(egui::TextEdit::singleline(&mut state.new_package_name).desired_width(200.0).hint_text("名称"));
+            if ui.button("➕ 添加/修改").clicked() {
+                match editor.set_package(&state.new_package_code, &state.new_package_name) {
+                    Ok(_) => {
+                        state.new_package_code.clear();
+                        state.new_package_name.clear();
+                    }
+                    Err(e) => error = Some(e.to_string()),
+                }
+            }
+        });
+
+        if error.is_some() {
+            self.success_message = None;
+            self.error_message = error;
         }
     }
 }
 
+// 版本号各字段的显示颜色（前缀、日期、PCB、DRAM、封装、颗粒数）
+const SEGMENT_COLORS: [egui::Color32; 6] = [
+    egui::Color32::GRAY,
+    egui::Color32::from_rgb(66, 133, 244),
+    egui::Color32::from_rgb(52, 168, 83),
+    egui::Color32::from_rgb(251, 140, 0),
+    egui::Color32::from_rgb(171, 71, 188),
+    egui::Color32::from_rgb(0, 151, 167),
+];
+
+// 版本号某一位置的代码表
+fn code_table_ui(ui: &mut egui::Ui, field: CodeField, limits: &CodecLimits) {
+    ui.strong(field.label());
+    egui::Grid::new(("code_table", field.label()))
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for (code, meaning) in field.code_table(limits) {
+                ui.monospace(code);
+                ui.label(meaning);
+                ui.end_row();
+            }
+        });
+}
+
+// 停用 / 恢复按钮：停用的代码保留在编码表中，仍可解析但不能再生成
+fn retire_button(ui: &mut egui::Ui, retired: bool) -> bool {
+    if retired {
+        ui.button("↩ 恢复").on_hover_text("已停用：仍可解析旧版本号，不能用于生成").clicked()
+    } else {
+        ui.button("🚫 停用").clicked()
+    }
+}
+
+fn toggle_retired<T: Ord>(retired: &mut BTreeSet<T>, value: T) {
+    if !retired.remove(&value) {
+        retired.insert(value);
+    }
+}
+
+// 字段无效时标红控件并在旁边显示错误
+fn mark_invalid(ui: &mut egui::Ui, response: &egui::Response, error: &str) -> egui::Response {
+    ui.painter().rect_stroke(response.rect.expand(2.0), 2.0, egui::Stroke::new(1.5, egui::Color32::RED));
+    ui.colored_label(egui::Color32::RED, error)
+}
+
 // 为 eframe::App trait 实现必要的方法
 impl eframe::App for SsdToolApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_dropped_files(ctx);
+        egui::SidePanel::right("history_panel")
+            .resizable(true)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                self.history_ui(ui);
+            });
         egui::CentralPanel::default().show(ctx, |ui| {
             self.ui(ui);
         });
@@ -0,0 +1,98 @@
+// build.rs
+// 根据 fwver.yaml 生成常量编码表 codebook.rs
+// fwver.yaml 是上层 config/fwver.yaml 的副本，放在本 crate 内以便单独打包发布
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+struct BaseDate {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+#[derive(Deserialize)]
+struct ChipCount {
+    min: u8,
+    max: u8,
+    codes: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FirmwareConfig {
+    base_date: BaseDate,
+    #[serde(default)]
+    rollover_base_dates: Vec<BaseDate>,
+    #[serde(default)]
+    era: usize,
+    base32_chars: String,
+    #[serde(default)]
+    date_encoding: Option<String>,
+    pcb_sizes: BTreeMap<u8, String>,
+    dram_sizes: BTreeMap<char, i32>,
+    packages: BTreeMap<char, String>,
+    chip_count: ChipCount,
+}
+
+fn main() {
+    let config_path = "fwver.yaml";
+    println!("cargo:rerun-if-changed={}", config_path);
+
+    let config_str = std::fs::read_to_string(config_path)
+        .expect("无法读取 fwver.yaml");
+    let config: FirmwareConfig = serde_yaml::from_str(&config_str)
+        .expect("无法解析 fwver.yaml");
+
+    // 当前纪元的基准日期
+    let base_date = match config.era {
+        0 => &config.base_date,
+        era => config.rollover_base_dates.get(era - 1).expect("era 超出 rollover_base_dates 范围"),
+    };
+    let date_encoding = match config.date_encoding.as_deref().unwrap_or("day_count") {
+        "day_count" => "DayCount",
+        "iso_week" => "IsoWeek",
+        "year_day" => "YearDay",
+        other => panic!("不支持的日期编码方式: {}", other),
+    };
+
+    // PCB 尺寸以 alphabet[尺寸] 编码，不能超出字符集长度
+    let pcb_sizes: Vec<String> = config.pcb_sizes.keys()
+        .inspect(|&&size| assert!(
+            usize::from(size) < config.base32_chars.len(),
+            "PCB尺寸 {} 超出可编码范围 (0-{})", size, config.base32_chars.len() - 1
+        ))
+        .map(|code| code.to_string())
+        .collect();
+    let dram_sizes: Vec<String> = config.dram_sizes.iter()
+        .map(|(code, size)| format!("(b'{}', {})", code.to_ascii_uppercase(), size))
+        .collect();
+    let packages: String = config.packages.keys()
+        .map(|code| code.to_ascii_uppercase())
+        .collect();
+
+    // 颗粒数量代码：第 n 个字符表示 n+1 个颗粒
+    let chip_codes = config.chip_count.codes.as_deref().unwrap_or("123456789ABCDEFG").to_ascii_uppercase();
+    assert!(
+        config.chip_count.min >= 1 && usize::from(config.chip_count.max) <= chip_codes.len(),
+        "颗粒数量范围 {}-{} 超出代码字符集 \"{}\"", config.chip_count.min, config.chip_count.max, chip_codes
+    );
+
+    let code = format!(
+        "Codebook {{\n    base_days: days_from_civil({}, {}, {}),\n    alphabet: b\"{}\",\n    date_encoding: DateEncoding::{},\n    pcb_sizes: &[{}],\n    dram_sizes: &[{}],\n    packages: b\"{}\",\n    chip_min: {},\n    chip_max: {},\n    chip_codes: b\"{}\",\n}}\n",
+        base_date.year,
+        base_date.month,
+        base_date.day,
+        config.base32_chars,
+        date_encoding,
+        pcb_sizes.join(", "),
+        dram_sizes.join(", "),
+        packages,
+        config.chip_count.min,
+        config.chip_count.max,
+        chip_codes,
+    );
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(format!("{}/codebook.rs", out_dir), code)
+        .expect("无法写入 codebook.rs");
+}
@@ -0,0 +1,502 @@
+// 固件版本号编解码核心（no_std，无堆分配）
+//
+// 供控制器固件直接解析自身的固件版本号使用，例如根据 DRAM 字段选择 DRAM 时序表。
+// 编码表默认由本 crate 内的 fwver.yaml（config/fwver.yaml 的副本）在构建时生成（见 DEFAULT_CODEBOOK）。
+#![no_std]
+
+// 固件版本号长度
+pub const FIRMWARE_CODE_LEN: usize = 8;
+// 日期编码长度
+pub const DATE_CODE_LEN: usize = 3;
+// 固件版本号前缀
+pub const FIRMWARE_PREFIX: u8 = b'S';
+// DRAMLess 对应的 DRAM 大小
+pub const DRAMLESS_MB: i32 = -1;
+
+// 编解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    InvalidDate,
+    DateOutOfRange,
+    InvalidDateCodeLength,
+    InvalidDateChar(u8),
+    InvalidDateCode,
+    InvalidFormat,
+    InvalidPcbSize,
+    InvalidDramSize,
+    InvalidDramCode,
+    InvalidPackage,
+    InvalidChipCount,
+    InvalidChipCountCode,
+}
+
+// 公历日期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+// 日期编码方式（均为 3 位）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateEncoding {
+    // 自基准日期起的天数
+    DayCount,
+    // 年（1 位，自基准日期所在 ISO 年起）+ ISO 周（2 位），解码为该周周一（基准日期所在的周解码为基准日期）
+    IsoWeek,
+    // 年（1 位，自基准年起）+ 年内第几天（2 位）
+    YearDay,
+}
+
+// 固件版本号各字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmwareFields {
+    pub date: Date,
+    pub pcb_size: u8,
+    pub dram_code: u8,
+    pub dram_size_mb: i32,
+    pub package_code: u8,
+    pub chip_count: u8,
+}
+
+// 编码表
+#[derive(Debug, Clone, Copy)]
+pub struct Codebook<'a> {
+    // 基准日期（自 1970-01-01 起的天数）
+    pub base_days: i64,
+    // 日期编码字符集
+    pub alphabet: &'a [u8],
+    // 日期编码方式
+    pub date_encoding: DateEncoding,
+    // 有效的 PCB 尺寸（版本号中以 alphabet[尺寸] 表示，0-9 之后为 A-V）
+    pub pcb_sizes: &'a [u8],
+    // DRAM 代码与大小(MB)
+    pub dram_sizes: &'a [(u8, i32)],
+    // 有效的封装代码
+    pub packages: &'a [u8],
+    // 颗粒数量范围
+    pub chip_min: u8,
+    pub chip_max: u8,
+    // 颗粒数量代码字符集（第 n 个字符表示 n+1 个颗粒）
+    pub chip_codes: &'a [u8],
+}
+
+// 由 fwver.yaml 生成的默认编码表
+pub const DEFAULT_CODEBOOK: Codebook<'static> = include!(concat!(env!("OUT_DIR"), "/codebook.rs"));
+
+pub const fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub const fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+pub const fn is_valid_date(year: i32, month: u32, day: u32) -> bool {
+    day >= 1 && day <= days_in_month(year, month)
+}
+
+// 公历日期转换为自 1970-01-01 起的天数
+pub const fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year as i64 - 1 } else { year as i64 };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// 自 1970-01-01 起的天数转换为公历日期
+pub const fn civil_from_days(days: i64) -> Date {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+    Date { year, month, day }
+}
+
+// 星期（周一为 0）
+pub const fn weekday_from_days(days: i64) -> i64 {
+    // 1970-01-01 为周四
+    (days + 3).rem_euclid(7)
+}
+
+// ISO 年与周数
+pub const fn iso_week_from_days(days: i64) -> (i32, u32) {
+    // 同一 ISO 周的周四所在年份即为 ISO 年
+    let thursday = days - weekday_from_days(days) + 3;
+    let year = civil_from_days(thursday).year;
+    let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+    (year, week as u32)
+}
+
+// ISO 年第 week 周周一的天数
+pub const fn iso_week_start(year: i32, week: u32) -> i64 {
+    let jan4 = days_from_civil(year, 1, 4);
+    jan4 - weekday_from_days(jan4) + (week as i64 - 1) * 7
+}
+
+// ISO 年的周数（52 或 53）
+pub const fn iso_weeks_in_year(year: i32) -> u32 {
+    iso_week_from_days(days_from_civil(year, 12, 28)).1
+}
+
+pub const fn days_in_year(year: i32) -> u32 {
+    if is_leap_year(year) { 366 } else { 365 }
+}
+
+impl<'a> Codebook<'a> {
+    // 3 位日期编码的取值个数（字符集长度的 3 次方）
+    pub fn date_capacity(&self) -> i64 {
+        let base = self.alphabet.len() as i64;
+        base * base * base
+    }
+
+    // 可编码的第一天（基准日期）
+    pub fn first_day(&self) -> i64 {
+        self.base_days
+    }
+
+    // 可编码的最后一天（自 1970-01-01 起的天数）
+    pub fn last_day(&self) -> i64 {
+        let years = self.alphabet.len() as i32;
+        match self.date_encoding {
+            DateEncoding::DayCount => self.base_days + self.date_capacity() - 1,
+            DateEncoding::IsoWeek => {
+                let last_year = iso_week_from_days(self.base_days).0 + years - 1;
+                iso_week_start(last_year, iso_weeks_in_year(last_year)) + 6
+            }
+            DateEncoding::YearDay => {
+                let last_year = civil_from_days(self.base_days).year + years - 1;
+                days_from_civil(last_year, 12, 31)
+            }
+        }
+    }
+
+    // 编码方式所需的字符集长度是否足够（后两位需容纳周数或年内天数）
+    pub fn supports_encoding(&self) -> bool {
+        let base = self.alphabet.len();
+        match self.date_encoding {
+            DateEncoding::DayCount => base > 0,
+            DateEncoding::IsoWeek => base * base > 53,
+            DateEncoding::YearDay => base * base > 366,
+        }
+    }
+
+    pub fn is_valid_pcb_size(&self, size: u8) -> bool {
+        self.pcb_sizes.contains(&size) && usize::from(size) < self.alphabet.len()
+    }
+
+    pub fn pcb_size_to_char(&self, size: u8) -> Result<u8, Error> {
+        if !self.is_valid_pcb_size(size) {
+            return Err(Error::InvalidPcbSize);
+        }
+        Ok(self.alphabet[usize::from(size)])
+    }
+
+    pub fn char_to_pcb_size(&self, c: u8) -> Result<u8, Error> {
+        let upper_c = c.to_ascii_uppercase();
+        self.alphabet.iter()
+            .position(|&ch| ch == upper_c)
+            .and_then(|pos| u8::try_from(pos).ok())
+            .filter(|&size| self.is_valid_pcb_size(size))
+            .ok_or(Error::InvalidPcbSize)
+    }
+
+    pub fn is_valid_package(&self, code: u8) -> bool {
+        self.packages.contains(&code.to_ascii_uppercase())
+    }
+
+    pub fn is_valid_chip_count(&self, count: u8) -> bool {
+        count >= self.chip_min && count <= self.chip_max
+    }
+
+    // 颗粒数量转换为代码字符
+    pub fn chip_count_to_char(&self, count: u8) -> Result<u8, Error> {
+        if !self.is_valid_chip_count(count) {
+            return Err(Error::InvalidChipCount);
+        }
+        usize::from(count).checked_sub(1)
+            .and_then(|index| self.chip_codes.get(index))
+            .copied()
+            .ok_or(Error::InvalidChipCount)
+    }
+
+    // 代码字符转换为颗粒数量
+    pub fn char_to_chip_count(&self, c: u8) -> Result<u8, Error> {
+        let upper_c = c.to_ascii_uppercase();
+        self.chip_codes.iter()
+            .position(|&ch| ch == upper_c)
+            .and_then(|pos| u8::try_from(pos + 1).ok())
+            .ok_or(Error::InvalidChipCountCode)
+    }
+
+    pub fn dram_code_to_size(&self, code: u8) -> Result<i32, Error> {
+        let code = code.to_ascii_uppercase();
+        self.dram_sizes.iter()
+            .find(|&&(c, _)| c == code)
+            .map(|&(_, size)| size)
+            .ok_or(Error::InvalidDramCode)
+    }
+
+    pub fn dram_size_to_code(&self, size_mb: i32) -> Result<u8, Error> {
+        self.dram_sizes.iter()
+            .find(|&&(_, size)| size == size_mb)
+            .map(|&(code, _)| code)
+            .ok_or(Error::InvalidDramSize)
+    }
+
+    pub fn date_to_code(&self, date: Date) -> Result<[u8; DATE_CODE_LEN], Error> {
+        if !is_valid_date(date.year, date.month, date.day) {
+            return Err(Error::InvalidDate);
+        }
+
+        let days = days_from_civil(date.year, date.month, date.day);
+        if days < self.first_day() || days > self.last_day() || !self.supports_encoding() {
+            return Err(Error::DateOutOfRange);
+        }
+
+        let base = self.alphabet.len() as i64;
+        let value = match self.date_encoding {
+            DateEncoding::DayCount => days - self.base_days,
+            DateEncoding::IsoWeek => {
+                let (year, week) = iso_week_from_days(days);
+                let year_index = (year - iso_week_from_days(self.base_days).0) as i64;
+                year_index * base * base + week as i64
+            }
+            DateEncoding::YearDay => {
+                let year_index = (date.year - civil_from_days(self.base_days).year) as i64;
+                let day_of_year = days - days_from_civil(date.year, 1, 1) + 1;
+                year_index * base * base + day_of_year
+            }
+        };
+
+        let mut value = value;
+        let mut code = [0u8; DATE_CODE_LEN];
+        for slot in code.iter_mut().rev() {
+            *slot = self.alphabet[(value % base) as usize];
+            value /= base;
+        }
+
+        Ok(code)
+    }
+
+    pub fn code_to_date(&self, code: &[u8]) -> Result<Date, Error> {
+        if code.len() != DATE_CODE_LEN {
+            return Err(Error::InvalidDateCodeLength);
+        }
+
+        let base = self.alphabet.len() as i64;
+        let mut value: i64 = 0;
+        for &c in code {
+            let upper_c = c.to_ascii_uppercase();
+            let pos = self.alphabet.iter()
+                .position(|&ch| ch == upper_c)
+                .ok_or(Error::InvalidDateChar(c))?;
+            value = value * base + pos as i64;
+        }
+
+        let year_index = (value / (base * base)) as i32;
+        let low = (value % (base * base)) as u32;
+        let days = match self.date_encoding {
+            DateEncoding::DayCount => self.base_days + value,
+            DateEncoding::IsoWeek => {
+                let year = iso_week_from_days(self.base_days).0 + year_index;
+                if low == 0 || low > iso_weeks_in_year(year) {
+                    return Err(Error::InvalidDateCode);
+                }
+                // 基准日期所在的周也可使用，解码为该周内不早于基准日期的第一天
+                let start = iso_week_start(year, low);
+                if start + 6 < self.base_days {
+                    return Err(Error::InvalidDateCode);
+                }
+                start.max(self.base_days)
+            }
+            DateEncoding::YearDay => {
+                let year = civil_from_days(self.base_days).year + year_index;
+                if low == 0 || low > days_in_year(year) {
+                    return Err(Error::InvalidDateCode);
+                }
+                let days = days_from_civil(year, 1, 1) + low as i64 - 1;
+                if days < self.base_days {
+                    return Err(Error::InvalidDateCode);
+                }
+                days
+            }
+        };
+
+        Ok(civil_from_days(days))
+    }
+
+    pub fn encode(
+        &self,
+        date: Date,
+        pcb_size: u8,
+        dram_size_mb: i32,
+        package_code: u8,
+        chip_count: u8,
+    ) -> Result<[u8; FIRMWARE_CODE_LEN], Error> {
+        let pcb_char = self.pcb_size_to_char(pcb_size)?;
+        if !self.is_valid_chip_count(chip_count) {
+            return Err(Error::InvalidChipCount);
+        }
+        if !self.is_valid_package(package_code) {
+            return Err(Error::InvalidPackage);
+        }
+
+        let date_code = self.date_to_code(date)?;
+        let dram_code = self.dram_size_to_code(dram_size_mb)?;
+        let chip_char = self.chip_count_to_char(chip_count)?;
+
+        Ok([
+            FIRMWARE_PREFIX,
+            date_code[0],
+            date_code[1],
+            date_code[2],
+            pcb_char,
+            dram_code,
+            package_code.to_ascii_uppercase(),
+            chip_char,
+        ])
+    }
+
+    pub fn decode(&self, code: &[u8; FIRMWARE_CODE_LEN]) -> Result<FirmwareFields, Error> {
+        if code[0] != FIRMWARE_PREFIX {
+            return Err(Error::InvalidFormat);
+        }
+
+        let date = self.code_to_date(&code[1..4])?;
+
+        let pcb_size = self.char_to_pcb_size(code[4])?;
+
+        let dram_code = code[5].to_ascii_uppercase();
+        let dram_size_mb = self.dram_code_to_size(dram_code)?;
+
+        let package_code = code[6].to_ascii_uppercase();
+        if !self.is_valid_package(package_code) {
+            return Err(Error::InvalidPackage);
+        }
+
+        let chip_count = self.char_to_chip_count(code[7])?;
+        if !self.is_valid_chip_count(chip_count) {
+            return Err(Error::InvalidChipCount);
+        }
+
+        Ok(FirmwareFields {
+            date,
+            pcb_size,
+            dram_code,
+            dram_size_mb,
+            package_code,
+            chip_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+    // 基准日期 2010-04-15 为周四
+    fn codebook(date_encoding: DateEncoding) -> Codebook<'static> {
+        Codebook {
+            base_days: days_from_civil(2010, 4, 15),
+            alphabet: ALPHABET,
+            date_encoding,
+            pcb_sizes: &[1, 2],
+            dram_sizes: &[(b'X', DRAMLESS_MB), (b'3', 1024)],
+            packages: b"AB",
+            chip_min: 1,
+            chip_max: 4,
+            chip_codes: b"1234",
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), date(2024, 2, 29));
+        assert_eq!(weekday_from_days(days_from_civil(2010, 4, 15)), 3);
+        // 2021-01-03 属于 ISO 2020 年第 53 周
+        assert_eq!(iso_week_from_days(days_from_civil(2021, 1, 3)), (2020, 53));
+        assert_eq!(iso_week_start(2020, 53), days_from_civil(2020, 12, 28));
+    }
+
+    #[test]
+    fn iso_week_base_week_decodes_to_base_date() {
+        let book = codebook(DateEncoding::IsoWeek);
+        let code = book.date_to_code(date(2010, 4, 15)).unwrap();
+        assert_eq!(book.date_to_code(date(2010, 4, 18)).unwrap(), code);
+        // 该周周一早于基准日期，不能编码
+        assert_eq!(book.date_to_code(date(2010, 4, 12)), Err(Error::DateOutOfRange));
+
+        let decoded = book.code_to_date(&code).unwrap();
+        assert_eq!(decoded, date(2010, 4, 15));
+        assert_eq!(book.date_to_code(decoded).unwrap(), code);
+
+        // 之后的周解码为周一
+        let next = book.date_to_code(date(2010, 4, 21)).unwrap();
+        assert_eq!(book.code_to_date(&next).unwrap(), date(2010, 4, 19));
+    }
+
+    #[test]
+    fn decoded_dates_encode_back_to_same_code() {
+        for encoding in [DateEncoding::DayCount, DateEncoding::IsoWeek, DateEncoding::YearDay] {
+            let book = codebook(encoding);
+            assert!(book.supports_encoding());
+            let mut day = book.first_day();
+            while day <= book.last_day() {
+                let code = book.date_to_code(civil_from_days(day)).unwrap();
+                let decoded = book.code_to_date(&code).unwrap();
+                assert_eq!(book.date_to_code(decoded), Ok(code), "{:?} {}", encoding, day);
+                day += 5;
+            }
+            assert_eq!(book.date_to_code(civil_from_days(book.last_day() + 1)), Err(Error::DateOutOfRange));
+        }
+    }
+
+    #[test]
+    fn rejects_codes_outside_encoding() {
+        let book = codebook(DateEncoding::YearDay);
+        // 年内第 0 天与非闰年的第 366 天
+        assert_eq!(book.code_to_date(b"000"), Err(Error::InvalidDateCode));
+        assert_eq!(book.code_to_date(b"1BE"), Err(Error::InvalidDateCode));
+        assert_eq!(book.code_to_date(b"00"), Err(Error::InvalidDateCodeLength));
+        assert_eq!(book.code_to_date(b"00W"), Err(Error::InvalidDateChar(b'W')));
+    }
+
+    #[test]
+    fn encode_and_decode_firmware_code() {
+        let book = codebook(DateEncoding::DayCount);
+        let code = book.encode(date(2010, 4, 16), 2, 1024, b'b', 4).unwrap();
+        assert_eq!(&code, b"S00123B4");
+        let fields = book.decode(&code).unwrap();
+        assert_eq!(fields.date, date(2010, 4, 16));
+        assert_eq!((fields.pcb_size, fields.dram_size_mb, fields.package_code, fields.chip_count), (2, 1024, b'B', 4));
+
+        assert_eq!(book.encode(date(2010, 4, 16), 3, 1024, b'B', 4), Err(Error::InvalidPcbSize));
+        assert_eq!(book.encode(date(2010, 4, 16), 1, 512, b'B', 4), Err(Error::InvalidDramSize));
+        assert_eq!(book.encode(date(2010, 4, 16), 1, 1024, b'C', 4), Err(Error::InvalidPackage));
+        assert_eq!(book.encode(date(2010, 4, 16), 1, 1024, b'B', 5), Err(Error::InvalidChipCount));
+        assert_eq!(book.decode(b"T00123B4"), Err(Error::InvalidFormat));
+    }
+}